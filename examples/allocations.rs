@@ -0,0 +1,138 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy_webcam_facial::*;
+use image::{GrayImage, RgbImage};
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 480;
+const RUN_TIME: Duration = Duration::from_secs(3);
+
+// Counts heap allocations and allocated bytes of whole process
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Copies raw camera-like luminance into frame buffer, like V4L sources do,
+// with reuse frame returned after detection is filled again instead of new buffer
+struct RawSource {
+    raw: Arc<Vec<u8>>,
+    reuse: bool,
+    spare: Option<GrayImage>,
+}
+
+impl FrameSource for RawSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        Ok(RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+            let value = self.raw[(y * WIDTH + x) as usize];
+            image::Rgb([value, value, value])
+        }))
+    }
+
+    fn next_detection_frame(&mut self) -> Result<CapturedFrame, WebcamFacialError> {
+        let mut buffer = self
+            .spare
+            .take()
+            .map(GrayImage::into_raw)
+            .unwrap_or_default();
+        buffer.clear();
+        buffer.extend_from_slice(&self.raw);
+        GrayImage::from_raw(WIDTH, HEIGHT, buffer)
+            .map(CapturedFrame::Luma)
+            .ok_or_else(|| WebcamFacialError::MalformedFrame("short frame".to_string()))
+    }
+
+    fn recycle(&mut self, frame: CapturedFrame) {
+        if let (true, CapturedFrame::Luma(frame)) = (self.reuse, frame) {
+            self.spare = Some(frame);
+        }
+    }
+}
+
+// Heap allocations and bytes per second and per frame with and without frame buffer reuse:
+// cargo run --release --example allocations
+fn main() {
+    let raw = Arc::new(
+        GrayImage::from_fn(WIDTH, HEIGHT, |x, y| image::Luma([((x + y) / 5) as u8])).into_raw(),
+    );
+    println!(
+        "{:>8} {:>8} {:>12} {:>10} {:>10}",
+        "reuse", "fps", "allocs/s", "per frame", "KiB/frame"
+    );
+    for reuse in [false, true] {
+        let raw = raw.clone();
+        let mut engine = WebcamFacialEngine::new(&WebcamFacialConfig {
+            config_frame_source: WebcamFacialSource::Custom(Arc::new(move || {
+                Ok(Box::new(RawSource {
+                    raw: raw.clone(),
+                    reuse,
+                    spare: None,
+                }) as Box<dyn FrameSource>)
+            })),
+            config_webcam_width: WIDTH,
+            config_webcam_height: HEIGHT,
+            config_filter_type: SmoothingFilterType::NoFilter,
+            ..Default::default()
+        });
+        if let Err(error) = engine.start() {
+            eprintln!("{}", error);
+            return;
+        }
+        // Skip detector loading, count steady capture loop only
+        thread::sleep(Duration::from_secs(1));
+        engine.poll();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let started = Instant::now();
+        while engine.is_running() && started.elapsed() < RUN_TIME {
+            engine.poll();
+            thread::sleep(Duration::from_millis(10));
+        }
+        let per_second = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) as f32
+            / started.elapsed().as_secs_f32();
+        let bytes_per_second = (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) as f32
+            / started.elapsed().as_secs_f32();
+        let stats = *engine.stats();
+        engine.stop();
+        while let Some(error) = engine.poll_error() {
+            eprintln!("{}", error);
+        }
+        println!(
+            "{:>8} {:>8.1} {:>12.0} {:>10.1} {:>10.1}",
+            reuse,
+            stats.fps(),
+            per_second,
+            per_second / stats.fps().max(f32::EPSILON),
+            bytes_per_second / 1024.0 / stats.fps().max(f32::EPSILON)
+        );
+    }
+}
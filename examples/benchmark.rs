@@ -0,0 +1,88 @@
+use std::{
+    env,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy_webcam_facial::*;
+use image::{imageops, RgbImage};
+
+const RESOLUTIONS: [(u32, u32); 4] = [(320, 240), (640, 480), (1280, 720), (1920, 1080)];
+const DETECTION_SCALES: [f32; 3] = [1.0, 0.5, 0.25];
+const RUN_TIME: Duration = Duration::from_secs(3);
+
+// Same frame over and over without waiting, so only detection limits the rate
+struct StillSource(RgbImage);
+
+impl FrameSource for StillSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        Ok(self.0.clone())
+    }
+}
+
+// Detection throughput for resolution and detection scale combinations,
+// run with photo containing face: cargo run --release --example benchmark -- face.jpg
+fn main() {
+    let frame = match env::args().nth(1) {
+        Some(path) => match image::open(&path) {
+            Ok(image) => image.to_rgb8(),
+            Err(error) => {
+                eprintln!("Can't open {}: {}", path, error);
+                return;
+            }
+        },
+        None => {
+            println!("No image given, measuring on gradient frame without face");
+            RgbImage::from_fn(640, 480, |x, y| {
+                image::Rgb([(x / 3) as u8, (y / 2) as u8, 128])
+            })
+        }
+    };
+    println!(
+        "{:>10} {:>6} {:>8} {:>13} {:>6}",
+        "resolution", "scale", "fps", "detection ms", "face"
+    );
+    for (width, height) in RESOLUTIONS {
+        let resized = imageops::resize(&frame, width, height, imageops::FilterType::Triangle);
+        for detection_scale in DETECTION_SCALES {
+            let resized = resized.clone();
+            let mut engine = WebcamFacialEngine::new(&WebcamFacialConfig {
+                config_frame_source: WebcamFacialSource::Custom(Arc::new(move || {
+                    Ok(Box::new(StillSource(resized.clone())) as Box<dyn FrameSource>)
+                })),
+                config_webcam_width: width,
+                config_webcam_height: height,
+                config_detection_scale: detection_scale,
+                config_filter_type: SmoothingFilterType::NoFilter,
+                ..Default::default()
+            });
+            if let Err(error) = engine.start() {
+                eprintln!("{}", error);
+                return;
+            }
+            let started = Instant::now();
+            let mut face_found = false;
+            while engine.is_running() && started.elapsed() < RUN_TIME {
+                if let Some(data) = engine.poll() {
+                    face_found |= data.face_found;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            // Stats are cleared when capture stops
+            let stats = *engine.stats();
+            engine.stop();
+            while let Some(error) = engine.poll_error() {
+                eprintln!("{}", error);
+            }
+            println!(
+                "{:>10} {:>6.2} {:>8.1} {:>13.1} {:>6}",
+                format!("{}x{}", width, height),
+                detection_scale,
+                stats.fps(),
+                stats.detection_ms(),
+                face_found
+            );
+        }
+    }
+}
@@ -22,6 +22,7 @@ fn main() {
             // Using LowPass filter, with value of 'alpha' at 0.01 for last 20 frames to get more smoothing
             config_filter_length: 20,
             config_filter_type: SmoothingFilterType::LowPass(0.01),
+            ..default()
         })
         // Using HookPlugin to get named object from loaded gltf scene
         .add_plugins(HookPlugin)
@@ -122,9 +123,10 @@ fn set_camera_position_from_plugin(
 // Keyboard control for plugin stop/start and app
 fn user_input_to_plugin_control_system(
     keyboard_input: Res<Input<KeyCode>>,
-    mut webcam_facial_control: ResMut<WebcamFacialController>,
+    mut webcam_facial_controllers: Query<&mut WebcamFacialController>,
     mut exit: EventWriter<AppExit>,
 ) {
+    let mut webcam_facial_control = webcam_facial_controllers.single_mut();
     if keyboard_input.just_pressed(KeyCode::A) {
         webcam_facial_control.control = true;
     }
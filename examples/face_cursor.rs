@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use bevy_webcam_facial::*;
+
+#[derive(Component)]
+struct FaceCursorMarker;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        // Smoothed data gives steady cursor, turn head less with higher sensitivity
+        .add_plugins(WebcamFacialPlugin {
+            config_filter_type: SmoothingFilterType::OneEuro {
+                min_cutoff: 1.0,
+                beta: 0.5,
+                d_cutoff: 1.0,
+            },
+            config_cursor_sensitivity: 2.0,
+            ..default()
+        })
+        .add_systems(Startup, setup)
+        // Read cursor after plugin updated it in the same frame
+        .add_systems(Update, move_marker.after(WebcamFacialSet))
+        .run();
+}
+
+/// set up 2D camera and cursor marker
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::RED,
+                custom_size: Some(Vec2::splat(20.0)),
+                ..default()
+            },
+            ..default()
+        },
+        FaceCursorMarker,
+    ));
+}
+
+fn move_marker(
+    cursor: Res<WebcamFacialCursor>,
+    windows: Query<&Window>,
+    mut query: Query<(&mut Transform, &mut Sprite), With<FaceCursorMarker>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    for (mut transform, mut sprite) in &mut query {
+        // Window coordinates (top-left origin, y down) to 2D world (center origin, y up)
+        transform.translation.x = cursor.position.x - window.width() / 2.0;
+        transform.translation.y = window.height() / 2.0 - cursor.position.y;
+        // Grey while face isn't seen
+        sprite.color = if cursor.face_found {
+            Color::RED
+        } else {
+            Color::GRAY
+        };
+    }
+}
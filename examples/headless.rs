@@ -0,0 +1,36 @@
+use std::{thread, time::Duration};
+
+use bevy_webcam_facial::*;
+
+// Print face position in terminal without Bevy App
+fn main() {
+    let mut engine = WebcamFacialEngine::new(
+        &WebcamFacialConfig::default().with_filter(SmoothingFilterType::LowPass(0.2), 10),
+    );
+    if let Err(error) = engine.start() {
+        eprintln!("{}", error);
+        return;
+    }
+    while engine.is_running() {
+        while let Some(error) = engine.poll_error() {
+            eprintln!("{}", error);
+        }
+        if let Some(data) = engine.poll() {
+            if data.face_found {
+                println!(
+                    "Face at {:.2} {:.2}, {:.1} fps",
+                    data.normalized_x,
+                    data.normalized_y,
+                    engine.stats().fps()
+                );
+            } else {
+                println!("No face");
+            }
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    // Setup errors arrive after capture stopped
+    while let Some(error) = engine.poll_error() {
+        eprintln!("{}", error);
+    }
+}
@@ -20,6 +20,7 @@ fn main() {
             config_webcam_autostart: true,
             config_filter_length: 15,
             config_filter_type: SmoothingFilterType::LowPass(0.1),
+            ..default()
         })
         .add_plugins(HookPlugin)
         .add_systems(Startup, load_scene)
@@ -0,0 +1,137 @@
+use image::GrayImage;
+
+use crate::DetectedFace;
+
+// Eye areas in face box as fractions of its size (x from, x to, y from, y to), image left eye first
+const EYE_AREAS: [(f32, f32, f32, f32); 2] = [(0.18, 0.45, 0.25, 0.48), (0.55, 0.82, 0.25, 0.48)];
+// Openness samples averaged for open eye baseline before transitions are reported
+const BASELINE_SAMPLES: u32 = 10;
+// How fast open eye baseline follows lighting and distance changes
+const BASELINE_RATE: f32 = 0.05;
+// Closed eye has to open above this part of baseline, so noise around threshold doesn't flicker
+const OPEN_HYSTERESIS: f32 = 1.25;
+
+// Eye closure of the tracked face (user's left and right eye)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WebcamFacialEyes {
+    pub left_closed: bool,
+    pub right_closed: bool,
+}
+
+impl WebcamFacialEyes {
+    // Both eyes closed
+    pub const fn blinking(&self) -> bool {
+        self.left_closed && self.right_closed
+    }
+}
+
+// Eye openness heuristic without landmarks: open eye (iris, sclera, eyelid edges) has strong
+// vertical contrast, closed eye is mostly skin. Openness is compared to open eye baseline of the face.
+pub(crate) struct WebcamFacialBlinkDetector {
+    threshold: f32,
+    mirrored: bool,
+    baseline: [f32; 2],
+    samples: u32,
+    eyes: WebcamFacialEyes,
+}
+
+impl WebcamFacialBlinkDetector {
+    // mirrored - frame is flipped horizontally, user's left eye is on the image left
+    pub(crate) fn new(threshold: f32, mirrored: bool) -> Self {
+        Self {
+            threshold: threshold.clamp(0.0, 1.0),
+            mirrored,
+            baseline: [0.0; 2],
+            samples: 0,
+            eyes: WebcamFacialEyes::default(),
+        }
+    }
+
+    // Start from scratch, face may be a different one
+    pub(crate) fn reset(&mut self) {
+        self.baseline = [0.0; 2];
+        self.samples = 0;
+        self.eyes = WebcamFacialEyes::default();
+    }
+
+    // Update with tracked face in image coordinates, returns eyes when their state changed
+    pub(crate) fn update(
+        &mut self,
+        image: &GrayImage,
+        face: Option<&DetectedFace>,
+    ) -> Option<WebcamFacialEyes> {
+        let Some(face) = face else {
+            // Eyes of missing face are reported open again
+            let changed = self.eyes != WebcamFacialEyes::default();
+            self.reset();
+            return changed.then_some(self.eyes);
+        };
+        let mut closed = [false; 2];
+        for (eye, &area) in EYE_AREAS.iter().enumerate() {
+            let openness = eye_openness(image, face, area)?;
+            let was_closed = if (eye == 0) == self.mirrored {
+                self.eyes.left_closed
+            } else {
+                self.eyes.right_closed
+            };
+            // Average first samples, eyes are assumed open while face is acquired
+            if self.samples < BASELINE_SAMPLES {
+                self.baseline[eye] += (openness - self.baseline[eye]) / (self.samples + 1) as f32;
+                continue;
+            }
+            let limit = if was_closed {
+                self.threshold * OPEN_HYSTERESIS
+            } else {
+                self.threshold
+            };
+            closed[eye] = openness < self.baseline[eye] * limit;
+            if !closed[eye] {
+                self.baseline[eye] += (openness - self.baseline[eye]) * BASELINE_RATE;
+            }
+        }
+        if self.samples < BASELINE_SAMPLES {
+            self.samples += 1;
+            return None;
+        }
+        // Image left eye is user's right one unless frame is mirrored
+        let (left, right) = if self.mirrored {
+            (closed[0], closed[1])
+        } else {
+            (closed[1], closed[0])
+        };
+        let eyes = WebcamFacialEyes {
+            left_closed: left,
+            right_closed: right,
+        };
+        let changed = eyes != self.eyes;
+        self.eyes = eyes;
+        changed.then_some(eyes)
+    }
+}
+
+// Mean absolute vertical gradient of eye area relative to its brightness, None if area is outside image
+fn eye_openness(
+    image: &GrayImage,
+    face: &DetectedFace,
+    (from_x, to_x, from_y, to_y): (f32, f32, f32, f32),
+) -> Option<f32> {
+    let (image_width, image_height) = (image.width() as i32, image.height() as i32);
+    let left = (face.x + (face.width as f32 * from_x) as i32).max(0);
+    let right = (face.x + (face.width as f32 * to_x) as i32).min(image_width);
+    let top = (face.y + (face.height as f32 * from_y) as i32).max(0);
+    let bottom = (face.y + (face.height as f32 * to_y) as i32).min(image_height);
+    if right - left < 2 || bottom - top < 2 {
+        return None;
+    }
+    let mut gradient = 0u64;
+    let mut brightness = 0u64;
+    for y in top..bottom - 1 {
+        for x in left..right {
+            let pixel = image.get_pixel(x as u32, y as u32)[0];
+            let below = image.get_pixel(x as u32, y as u32 + 1)[0];
+            gradient += u64::from(pixel.abs_diff(below));
+            brightness += u64::from(pixel);
+        }
+    }
+    Some(gradient as f32 / (brightness as f32 + 1.0))
+}
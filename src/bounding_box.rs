@@ -0,0 +1,19 @@
+use bevy::math::{Rect, Vec2};
+
+use crate::{CoordinateMode, WebcamFacialData};
+
+impl WebcamFacialData {
+    // Face rectangle over camera preview of preview_size centered at origin (ex. sprite at 0,0),
+    // in Bevy 2D coordinates (y up). Draw with gizmos.rect_2d(rect.center(), 0.0, rect.size(), color)
+    pub fn bounding_box(&self, preview_size: Vec2) -> Option<Rect> {
+        if !self.face_found {
+            return None;
+        }
+        // Box itself, not normalized_x/y changed by sensitivity, calibration and deadzone.
+        // x is flipped (positive - left of frame), y grows down (CoordinateMode::CenterOriginYDown)
+        let center =
+            -Vec2::from(CoordinateMode::CenterOriginYDown.box_center(self)) * preview_size / 2.0;
+        let size = Vec2::new(self.normalized_width, self.normalized_height) * preview_size;
+        Some(Rect::from_center_size(center, size))
+    }
+}
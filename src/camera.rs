@@ -0,0 +1,1492 @@
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use image::{imageops, GrayImage, RgbImage};
+use log::{info, warn};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{ImageSequenceSource, WebcamFacialError, WebcamFacialSource, STOP_POLL_INTERVAL};
+
+// Pixel format requested from webcam
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WebcamFormat {
+    #[default]
+    Rgb, // RGB frames, converted from camera native format by driver (libv4l on Linux)
+    Mjpeg, // Motion JPEG frames decoded by plugin, higher resolutions on many USB webcams (Linux or 'nokhwa' feature)
+    Yuyv, // Raw YUYV frames, without preview grayscale is taken from Y channel skipping RGB conversion (Linux or 'nokhwa' feature)
+    Nv12, // Planar 4:2:0 with interleaved UV plane, often the only raw format of laptop cameras, Y plane used as grayscale
+    Yu12, // Planar 4:2:0 with separate U and V planes (I420), Y plane used as grayscale
+    Grey, // 8-bit grayscale (IR and machine vision cameras), fed to detector without conversion
+    Y16,  // 16-bit grayscale, reduced to 8-bit by taking high byte
+}
+
+#[cfg(target_os = "linux")]
+impl WebcamFormat {
+    // Native format streamed as is, None - any format converted by libv4l
+    const fn fourcc(self) -> Option<&'static [u8; 4]> {
+        match self {
+            Self::Rgb => None,
+            Self::Mjpeg => Some(b"MJPG"),
+            Self::Yuyv => Some(b"YUYV"),
+            Self::Nv12 => Some(b"NV12"),
+            Self::Yu12 => Some(b"YU12"),
+            Self::Grey => Some(b"GREY"),
+            Self::Y16 => Some(b"Y16 "),
+        }
+    }
+}
+
+// Frame passed from capture thread to detection
+pub enum CapturedFrame {
+    Rgb(RgbImage),
+    Luma(GrayImage), // Grayscale only, when RGB isn't needed and source has native luminance
+}
+
+impl CapturedFrame {
+    pub(crate) fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Self::Rgb(frame) => frame.dimensions(),
+            Self::Luma(frame) => frame.dimensions(),
+        }
+    }
+
+    pub(crate) fn flip(&mut self, horizontal: bool, vertical: bool) {
+        match self {
+            Self::Rgb(frame) => {
+                if horizontal {
+                    imageops::flip_horizontal_in_place(frame);
+                }
+                if vertical {
+                    imageops::flip_vertical_in_place(frame);
+                }
+            }
+            Self::Luma(frame) => {
+                if horizontal {
+                    imageops::flip_horizontal_in_place(frame);
+                }
+                if vertical {
+                    imageops::flip_vertical_in_place(frame);
+                }
+            }
+        }
+    }
+
+    // Grayscale region of frame written into reused buffer, region must be inside frame
+    pub(crate) fn luma_region(
+        &self,
+        (x, y, width, height): (u32, u32, u32, u32),
+        mut buffer: Vec<u8>,
+    ) -> GrayImage {
+        buffer.clear();
+        for row in y..y + height {
+            match self {
+                Self::Rgb(frame) => {
+                    buffer.extend((x..x + width).map(|column| {
+                        let rgb_pixel = frame.get_pixel(column, row);
+                        let gray_value = u32::from(rgb_pixel[0]) * 77
+                            + u32::from(rgb_pixel[1]) * 150
+                            + u32::from(rgb_pixel[2]) * 29;
+                        ((gray_value >> 8) & 0xFF) as u8
+                    }));
+                }
+                Self::Luma(frame) => {
+                    let start = (row * frame.width() + x) as usize;
+                    buffer.extend_from_slice(&frame.as_raw()[start..start + width as usize]);
+                }
+            }
+        }
+        GrayImage::from_raw(width, height, buffer).unwrap_or_default()
+    }
+
+    fn into_raw(self) -> Vec<u8> {
+        match self {
+            Self::Rgb(frame) => frame.into_raw(),
+            Self::Luma(frame) => frame.into_raw(),
+        }
+    }
+}
+
+// Captured frame going back to capture thread when dropped, so its buffer is reused for next capture
+pub struct RecycledFrame {
+    frame: Option<CapturedFrame>,
+    recycle_sender: Sender<CapturedFrame>,
+}
+
+impl Deref for RecycledFrame {
+    type Target = CapturedFrame;
+
+    fn deref(&self) -> &CapturedFrame {
+        self.frame.as_ref().expect("frame is taken only on drop")
+    }
+}
+
+impl DerefMut for RecycledFrame {
+    fn deref_mut(&mut self) -> &mut CapturedFrame {
+        self.frame.as_mut().expect("frame is taken only on drop")
+    }
+}
+
+impl Drop for RecycledFrame {
+    fn drop(&mut self) {
+        if let Some(frame) = self.frame.take() {
+            // Spare buffer is already waiting or capture thread is gone, just free it
+            let _ = self.recycle_sender.try_send(frame);
+        }
+    }
+}
+
+// Take buffer of returned frame for next capture if its size fits, allocate otherwise
+fn reuse_buffer(spare: &mut Option<CapturedFrame>, capacity: usize) -> Vec<u8> {
+    let mut buffer = spare
+        .take()
+        .map(CapturedFrame::into_raw)
+        .unwrap_or_default();
+    buffer.clear();
+    buffer.reserve(capacity);
+    buffer
+}
+
+// Webcam device found by list_webcam_devices()
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebcamDeviceInfo {
+    pub device: u32,             // Use as config_webcam_device
+    pub path: String,            // ex. "/dev/video0"
+    pub name: String,            // Human-readable device name
+    pub stable_ids: Vec<String>, // Names in /dev/v4l/by-id and by-path, use as config_webcam_device_id
+    pub modes: Vec<WebcamMode>,
+}
+
+// Capture mode supported by webcam device
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebcamMode {
+    pub format: String, // FourCC of native format, ex. "YUYV", "MJPG"
+    pub width: u32,
+    pub height: u32,
+    pub framerates: Vec<f64>,
+}
+
+// Find available webcam devices and their supported modes (Linux only, empty elsewhere)
+pub fn list_webcam_devices() -> Vec<WebcamDeviceInfo> {
+    #[cfg(target_os = "linux")]
+    {
+        let Ok(entries) = std::fs::read_dir("/dev") else {
+            return Vec::new();
+        };
+        let mut devices: Vec<WebcamDeviceInfo> = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let device = file_name.strip_prefix("video")?.parse().ok()?;
+                query_webcam_device(device)
+            })
+            .collect();
+        devices.sort_by_key(|info| info.device);
+        devices
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+// udev links to video devices named by USB serial (by-id) or by USB port (by-path)
+#[cfg(target_os = "linux")]
+const STABLE_ID_DIRECTORIES: [&str; 2] = ["/dev/v4l/by-id", "/dev/v4l/by-path"];
+
+// Current device number of webcam with stable id (name or full path of link in /dev/v4l/by-id
+// or /dev/v4l/by-path), stays correct when /dev/videoN numbers reorder across reboots (Linux only)
+pub fn resolve_webcam_device_id(id: &str) -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        STABLE_ID_DIRECTORIES.iter().find_map(|directory| {
+            let target = std::fs::canonicalize(std::path::Path::new(directory).join(id)).ok()?;
+            target
+                .file_name()?
+                .to_str()?
+                .strip_prefix("video")?
+                .parse()
+                .ok()
+        })
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = id;
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn webcam_stable_ids(device: u32) -> Vec<String> {
+    let path = std::path::PathBuf::from(format!("/dev/video{}", device));
+    STABLE_ID_DIRECTORIES
+        .iter()
+        .filter_map(|directory| std::fs::read_dir(directory).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| std::fs::canonicalize(entry.path()).is_ok_and(|target| target == path))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn query_webcam_device(device: u32) -> Option<WebcamDeviceInfo> {
+    let path = format!("/dev/video{}", device);
+    let camera = rscam::Camera::new(&path).ok()?;
+    let name = std::fs::read_to_string(format!("/sys/class/video4linux/video{}/name", device))
+        .map_or_else(|_| path.clone(), |name| name.trim().to_string());
+    let mut modes = Vec::new();
+    for format_info in camera.formats().filter_map(Result::ok) {
+        let format = String::from_utf8_lossy(&format_info.format).into_owned();
+        let resolutions = match camera.resolutions(&format_info.format) {
+            Ok(rscam::ResolutionInfo::Discretes(resolutions)) => resolutions,
+            Ok(rscam::ResolutionInfo::Stepwise { min, max, .. }) => vec![min, max],
+            Err(_) => continue,
+        };
+        for (width, height) in resolutions {
+            let framerates = match camera.intervals(&format_info.format, (width, height)) {
+                Ok(rscam::IntervalInfo::Discretes(intervals)) => intervals,
+                Ok(rscam::IntervalInfo::Stepwise { min, max, .. }) => vec![max, min],
+                Err(_) => Vec::new(),
+            }
+            .into_iter()
+            .map(|(numerator, denominator)| f64::from(denominator) / f64::from(numerator))
+            .collect();
+            modes.push(WebcamMode {
+                format: format.clone(),
+                width,
+                height,
+                framerates,
+            });
+        }
+    }
+    // Metadata nodes of the same camera have no capture formats
+    if modes.is_empty() {
+        return None;
+    }
+    Some(WebcamDeviceInfo {
+        device,
+        path,
+        name,
+        stable_ids: webcam_stable_ids(device),
+        modes,
+    })
+}
+
+// Adjustable camera setting (ex. "Brightness", "Exposure (Absolute)"), switches have range 0 .. 1
+// and menus range of item indexes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebcamControl {
+    pub id: u32,
+    pub name: String, // Name reported by driver, used by set_control
+    pub min: i64,
+    pub max: i64,
+    pub step: i64,
+    pub default: i64,
+    pub value: i64,
+}
+
+// Camera settings which can be read and set as numbers (Linux only), camera may be streaming
+pub(crate) fn webcam_controls(device: u32) -> Result<Vec<WebcamControl>, WebcamFacialError> {
+    #[cfg(target_os = "linux")]
+    {
+        let camera = open_control_device(device)?;
+        Ok(camera
+            .controls()
+            .filter_map(Result::ok)
+            .filter(|control| control.flags & (rscam::FLAG_DISABLED | rscam::FLAG_INACTIVE) == 0)
+            .filter_map(|control| {
+                let (min, max, step, default, value) = match control.data {
+                    rscam::CtrlData::Integer {
+                        value,
+                        default,
+                        minimum,
+                        maximum,
+                        step,
+                    } => (
+                        i64::from(minimum),
+                        i64::from(maximum),
+                        i64::from(step),
+                        i64::from(default),
+                        i64::from(value),
+                    ),
+                    rscam::CtrlData::Integer64 {
+                        value,
+                        default,
+                        minimum,
+                        maximum,
+                        step,
+                    } => (minimum, maximum, step, default, value),
+                    rscam::CtrlData::Boolean { value, default } => {
+                        (0, 1, 1, i64::from(default), i64::from(value))
+                    }
+                    rscam::CtrlData::Menu {
+                        value,
+                        default,
+                        ref items,
+                    } => (
+                        items.first().map_or(0, |item| i64::from(item.index)),
+                        items.last().map_or(0, |item| i64::from(item.index)),
+                        1,
+                        i64::from(default),
+                        i64::from(value),
+                    ),
+                    // Buttons, strings and control classes have no numeric value
+                    _ => return None,
+                };
+                Some(WebcamControl {
+                    id: control.id,
+                    name: control.name,
+                    min,
+                    max,
+                    step,
+                    default,
+                    value,
+                })
+            })
+            .collect())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = device;
+        Err(WebcamFacialError::UnsupportedControl(
+            "camera controls are available on Linux only".to_string(),
+        ))
+    }
+}
+
+// Set camera control by name (case insensitive), value is clamped to its range
+pub(crate) fn set_webcam_control(
+    device: u32,
+    name: &str,
+    value: i64,
+) -> Result<(), WebcamFacialError> {
+    #[cfg(target_os = "linux")]
+    {
+        let control = webcam_controls(device)?
+            .into_iter()
+            .find(|control| control.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                WebcamFacialError::UnsupportedControl(format!("'{}' on /dev/video{}", name, device))
+            })?;
+        let camera = open_control_device(device)?;
+        camera
+            .set_control(control.id, &value.clamp(control.min, control.max))
+            .map_err(|err| WebcamFacialError::UnsupportedControl(format!("'{}': {}", name, err)))
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (name, value);
+        webcam_controls(device).map(|_| ())
+    }
+}
+
+// Second handle of the device, V4L allows controls while capture streams
+#[cfg(target_os = "linux")]
+fn open_control_device(device: u32) -> Result<rscam::Camera, WebcamFacialError> {
+    let path = format!("/dev/video{}", device);
+    rscam::Camera::new(&path)
+        .map_err(|err| WebcamFacialError::CameraOpen(format!("'{}': {}", path, err)))
+}
+
+// Opened and started capture source returning RGB frames
+pub trait FrameSource {
+    // Blocks until next frame is captured
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError>;
+
+    // Frame used only for detection (no preview), sources with native luminance return
+    // CapturedFrame::Luma to skip RGB conversion
+    fn next_detection_frame(&mut self) -> Result<CapturedFrame, WebcamFacialError> {
+        self.next_frame().map(CapturedFrame::Rgb)
+    }
+
+    // Frame returned after detection, sources may keep its buffer to fill next frame into
+    fn recycle(&mut self, _frame: CapturedFrame) {}
+
+    // Stop streaming and release device
+    fn stop(&mut self) {}
+}
+
+// Mode camera delivers after negotiation with requested settings (controller active_config())
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveCameraConfig {
+    pub format: WebcamFormat,   // Rgb for files and custom sources
+    pub resolution: (u32, u32), // Size of first delivered frame, before rotation
+    pub framerate: u32, // Negotiated on Linux, requested on other platforms and with files or custom sources
+}
+
+// Requested webcam mode (config_webcam_*)
+#[derive(Clone, Debug)]
+pub struct CameraSettings {
+    pub device: u32,
+    pub resolutions: Vec<(u32, u32)>, // By priority, first supported one is used
+    pub framerate: u32,
+    pub format: WebcamFormat,
+    pub nearest_mode: bool,
+}
+
+// Open configured frame source, returns it with framerate it delivers
+pub fn open_frame_source(
+    source: &WebcamFacialSource,
+    settings: CameraSettings,
+) -> Result<(Box<dyn FrameSource>, u32), WebcamFacialError> {
+    let framerate = settings.framerate;
+    match source {
+        WebcamFacialSource::Camera => open_camera(
+            settings.device,
+            &settings.resolutions,
+            settings.framerate,
+            settings.format,
+            settings.nearest_mode,
+        ),
+        WebcamFacialSource::ImageDirectory(path) => {
+            ImageSequenceSource::from_directory(path, settings.framerate)
+                .map(|source| (Box::new(source) as Box<dyn FrameSource>, framerate))
+        }
+        WebcamFacialSource::Gif(path) => ImageSequenceSource::from_gif(path, settings.framerate)
+            .map(|source| (Box::new(source) as Box<dyn FrameSource>, framerate)),
+        WebcamFacialSource::Custom(factory) => factory().map(|source| (source, framerate)),
+    }
+}
+
+// Open camera with first resolution it supports, returns it with selected framerate
+fn open_camera(
+    camera_device: u32,
+    resolutions: &[(u32, u32)],
+    camera_framerate: u32,
+    camera_format: WebcamFormat,
+    nearest_mode: bool,
+) -> Result<(Box<dyn FrameSource>, u32), WebcamFacialError> {
+    #[cfg(target_os = "linux")]
+    {
+        let (camera_width, camera_height, camera_framerate) = resolve_webcam_mode(
+            camera_device,
+            resolutions,
+            camera_framerate,
+            camera_format,
+            nearest_mode,
+        )?;
+        open_camera_mode(
+            camera_device,
+            camera_width,
+            camera_height,
+            camera_framerate,
+            camera_format,
+        )
+        .map(|source| (source, camera_framerate))
+    }
+    // Other backends can't be queried, resolutions are tried in order,
+    // backend picks closest mode itself or reports unsupported one
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = nearest_mode;
+        let mut result = Err(WebcamFacialError::UnsupportedMode(
+            "no resolution requested".to_string(),
+            Vec::new(),
+        ));
+        for &(camera_width, camera_height) in resolutions {
+            result = open_camera_mode(
+                camera_device,
+                camera_width,
+                camera_height,
+                camera_framerate,
+                camera_format,
+            );
+            match &result {
+                Ok(_) => break,
+                Err(error) => info!(
+                    "Camera didn't open at {}x{}: {}",
+                    camera_width, camera_height, error
+                ),
+            }
+        }
+        result.map(|source| (source, camera_framerate))
+    }
+}
+
+// Open capture backend available on this platform for requested format
+fn open_camera_mode(
+    camera_device: u32,
+    camera_width: u32,
+    camera_height: u32,
+    camera_framerate: u32,
+    camera_format: WebcamFormat,
+) -> Result<Box<dyn FrameSource>, WebcamFacialError> {
+    #[cfg(all(feature = "nokhwa", not(target_os = "linux")))]
+    {
+        open_nokhwa_camera(
+            camera_device,
+            camera_width,
+            camera_height,
+            camera_framerate,
+            camera_format,
+        )
+        .map(|source| Box::new(source) as Box<dyn FrameSource>)
+    }
+    #[cfg(not(all(feature = "nokhwa", not(target_os = "linux"))))]
+    match camera_format {
+        #[cfg(any(target_os = "linux", all(windows, not(feature = "nokhwa"))))]
+        WebcamFormat::Rgb => {
+            get_camera_frame_iterator(camera_device, camera_width, camera_height, camera_framerate)
+                .map(|cam_iter| {
+                    Box::new(CameraCaptureSource(cam_iter, None)) as Box<dyn FrameSource>
+                })
+        }
+        #[cfg(target_os = "linux")]
+        WebcamFormat::Mjpeg => start_v4l_camera(
+            camera_device,
+            camera_width,
+            camera_height,
+            camera_framerate,
+            b"MJPG",
+        )
+        .map(|camera| Box::new(MjpegSource(camera)) as Box<dyn FrameSource>),
+        #[cfg(target_os = "linux")]
+        WebcamFormat::Yuyv => start_v4l_camera(
+            camera_device,
+            camera_width,
+            camera_height,
+            camera_framerate,
+            b"YUYV",
+        )
+        .map(|camera| Box::new(YuyvSource(camera, None)) as Box<dyn FrameSource>),
+        #[cfg(target_os = "linux")]
+        WebcamFormat::Nv12 | WebcamFormat::Yu12 => start_v4l_camera(
+            camera_device,
+            camera_width,
+            camera_height,
+            camera_framerate,
+            camera_format.fourcc().unwrap_or(b"NV12"),
+        )
+        .map(|camera| Box::new(Yuv420Source(camera, camera_format, None)) as Box<dyn FrameSource>),
+        #[cfg(target_os = "linux")]
+        WebcamFormat::Grey | WebcamFormat::Y16 => start_v4l_camera(
+            camera_device,
+            camera_width,
+            camera_height,
+            camera_framerate,
+            camera_format.fourcc().unwrap_or(b"GREY"),
+        )
+        .map(|camera| {
+            Box::new(GrayscaleSource(camera, camera_format, None)) as Box<dyn FrameSource>
+        }),
+        #[cfg(windows)]
+        WebcamFormat::Mjpeg
+        | WebcamFormat::Yuyv
+        | WebcamFormat::Nv12
+        | WebcamFormat::Yu12
+        | WebcamFormat::Grey
+        | WebcamFormat::Y16 => Err(WebcamFacialError::CameraStart(format!(
+            "{:?} format needs 'nokhwa' feature on this platform",
+            camera_format
+        ))),
+        #[cfg(not(any(target_os = "linux", windows)))]
+        _ => Err(WebcamFacialError::CameraOpen(
+            "no capture backend on this platform, enable 'nokhwa' feature".to_string(),
+        )),
+    }
+}
+
+// RGB frames converted by driver
+#[cfg(any(target_os = "linux", all(windows, not(feature = "nokhwa"))))]
+struct CameraCaptureSource(camera_capture::ImageIterator, Option<CapturedFrame>);
+
+#[cfg(any(target_os = "linux", all(windows, not(feature = "nokhwa"))))]
+impl FrameSource for CameraCaptureSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        let frame = self.0.next().ok_or_else(|| {
+            WebcamFacialError::CaptureFailed("camera returned no frame".to_string())
+        })?;
+        let (width, height) = frame.dimensions();
+        let mut buffer = reuse_buffer(&mut self.1, frame.len());
+        buffer.extend_from_slice(&frame);
+        RgbImage::from_raw(width, height, buffer).ok_or_else(|| {
+            WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+        })
+    }
+
+    fn recycle(&mut self, frame: CapturedFrame) {
+        self.1 = Some(frame);
+    }
+}
+
+// MJPEG frames from V4L decoded by plugin
+#[cfg(target_os = "linux")]
+struct MjpegSource(rscam::Camera);
+
+#[cfg(target_os = "linux")]
+impl FrameSource for MjpegSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        let frame = self
+            .0
+            .capture()
+            .map_err(|err| WebcamFacialError::CaptureFailed(err.to_string()))?;
+        image::load_from_memory_with_format(&frame, image::ImageFormat::Jpeg)
+            .map(|decoded| decoded.to_rgb8())
+            .map_err(|err| WebcamFacialError::MalformedFrame(format!("undecodable MJPEG: {}", err)))
+    }
+
+    fn stop(&mut self) {
+        if let Err(err) = self.0.stop() {
+            warn!("Error stopping camera: {}", err);
+        }
+    }
+}
+
+// Raw frame from V4L, rejected if shorter than frame_len for its resolution
+#[cfg(target_os = "linux")]
+fn capture_raw(
+    camera: &rscam::Camera,
+    frame_len: fn(usize, usize) -> usize,
+) -> Result<rscam::Frame, WebcamFacialError> {
+    let frame = camera
+        .capture()
+        .map_err(|err| WebcamFacialError::CaptureFailed(err.to_string()))?;
+    let (width, height) = frame.resolution;
+    if frame.len() < frame_len(width as usize, height as usize) {
+        return Err(WebcamFacialError::MalformedFrame(
+            "camera returned incomplete frame".to_string(),
+        ));
+    }
+    Ok(frame)
+}
+
+// BT.601 integer conversion of one pixel, u and v centered at 0
+#[cfg(target_os = "linux")]
+fn push_yuv_as_rgb(rgb: &mut Vec<u8>, luma: u8, u: i32, v: i32) {
+    let c = (i32::from(luma) - 16) * 298;
+    rgb.push(((c + 409 * v + 128) >> 8).clamp(0, 255) as u8);
+    rgb.push(((c - 100 * u - 208 * v + 128) >> 8).clamp(0, 255) as u8);
+    rgb.push(((c + 516 * u + 128) >> 8).clamp(0, 255) as u8);
+}
+
+// Two pixels share U and V: Y0 U Y1 V. Odd pixel count ends with half pair Y U,
+// it takes V of previous pair (neutral without one)
+#[cfg(target_os = "linux")]
+fn push_yuyv_as_rgb(rgb: &mut Vec<u8>, yuyv: &[u8]) {
+    let mut v = 0;
+    for pair in yuyv.chunks(4) {
+        match *pair {
+            [y0, u, y1, pair_v] => {
+                let u = i32::from(u) - 128;
+                v = i32::from(pair_v) - 128;
+                push_yuv_as_rgb(rgb, y0, u, v);
+                push_yuv_as_rgb(rgb, y1, u, v);
+            }
+            [y0, u, ..] => push_yuv_as_rgb(rgb, y0, i32::from(u) - 128, v),
+            _ => {}
+        }
+    }
+}
+
+// Raw YUYV frames from V4L, Y of every pixel is its luminance
+#[cfg(target_os = "linux")]
+struct YuyvSource(rscam::Camera, Option<CapturedFrame>);
+
+#[cfg(target_os = "linux")]
+impl YuyvSource {
+    fn capture(&self) -> Result<rscam::Frame, WebcamFacialError> {
+        capture_raw(&self.0, |width, height| width * height * 2)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FrameSource for YuyvSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        let frame = self.capture()?;
+        let (width, height) = frame.resolution;
+        let mut rgb = reuse_buffer(&mut self.1, (width * height * 3) as usize);
+        push_yuyv_as_rgb(&mut rgb, &frame[..(width * height * 2) as usize]);
+        RgbImage::from_raw(width, height, rgb).ok_or_else(|| {
+            WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+        })
+    }
+
+    fn next_detection_frame(&mut self) -> Result<CapturedFrame, WebcamFacialError> {
+        let frame = self.capture()?;
+        let (width, height) = frame.resolution;
+        let mut luma = reuse_buffer(&mut self.1, (width * height) as usize);
+        luma.extend(frame[..(width * height * 2) as usize].iter().step_by(2));
+        GrayImage::from_raw(width, height, luma)
+            .map(CapturedFrame::Luma)
+            .ok_or_else(|| {
+                WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+            })
+    }
+
+    fn recycle(&mut self, frame: CapturedFrame) {
+        self.1 = Some(frame);
+    }
+
+    fn stop(&mut self) {
+        if let Err(err) = self.0.stop() {
+            warn!("Error stopping camera: {}", err);
+        }
+    }
+}
+
+// Planar 4:2:0 frames from V4L (NV12 or YU12): full Y plane, then U and V for every 2x2 pixel block
+#[cfg(target_os = "linux")]
+struct Yuv420Source(rscam::Camera, WebcamFormat, Option<CapturedFrame>);
+
+#[cfg(target_os = "linux")]
+impl Yuv420Source {
+    fn capture(&self) -> Result<rscam::Frame, WebcamFacialError> {
+        capture_raw(&self.0, |width, height| {
+            width * height + 2 * width.div_ceil(2) * height.div_ceil(2)
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FrameSource for Yuv420Source {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        let frame = self.capture()?;
+        let (width, height) = frame.resolution;
+        let mut rgb = reuse_buffer(&mut self.2, (width * height * 3) as usize);
+        push_yuv420_as_rgb(&mut rgb, &frame, width, height, self.1)?;
+        RgbImage::from_raw(width, height, rgb).ok_or_else(|| {
+            WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+        })
+    }
+
+    fn next_detection_frame(&mut self) -> Result<CapturedFrame, WebcamFacialError> {
+        let frame = self.capture()?;
+        let (width, height) = frame.resolution;
+        let mut luma = reuse_buffer(&mut self.2, (width * height) as usize);
+        push_yuv420_luma(&mut luma, &frame, width, height)?;
+        GrayImage::from_raw(width, height, luma)
+            .map(CapturedFrame::Luma)
+            .ok_or_else(|| {
+                WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+            })
+    }
+
+    fn recycle(&mut self, frame: CapturedFrame) {
+        self.2 = Some(frame);
+    }
+
+    fn stop(&mut self) {
+        if let Err(err) = self.0.stop() {
+            warn!("Error stopping camera: {}", err);
+        }
+    }
+}
+
+// Y, U and V planes of 4:2:0 frame, chroma has one sample for every 2x2 pixel block
+#[cfg(target_os = "linux")]
+fn yuv420_planes(
+    frame: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(&[u8], &[u8]), WebcamFacialError> {
+    let luma_len = (width * height) as usize;
+    let chroma_len = 2 * (width.div_ceil(2) * height.div_ceil(2)) as usize;
+    if width == 0 || height == 0 || frame.len() < luma_len + chroma_len {
+        return Err(WebcamFacialError::MalformedFrame(format!(
+            "{} bytes don't make {}x{} 4:2:0 frame",
+            frame.len(),
+            width,
+            height
+        )));
+    }
+    Ok((&frame[..luma_len], &frame[luma_len..luma_len + chroma_len]))
+}
+
+// Planar 4:2:0 (NV12 - U V pairs in one plane, YU12 - U plane followed by V plane) to RGB
+#[cfg(target_os = "linux")]
+fn push_yuv420_as_rgb(
+    rgb: &mut Vec<u8>,
+    frame: &[u8],
+    width: u32,
+    height: u32,
+    format: WebcamFormat,
+) -> Result<(), WebcamFacialError> {
+    let (luma_plane, chroma) = yuv420_planes(frame, width, height)?;
+    let chroma_width = width.div_ceil(2) as usize;
+    let chroma_plane_len = chroma.len() / 2;
+    for (row, luma_row) in luma_plane.chunks_exact(width as usize).enumerate() {
+        for (column, &luma) in luma_row.iter().enumerate() {
+            let block = row / 2 * chroma_width + column / 2;
+            let (u, v) = match format {
+                WebcamFormat::Nv12 => (chroma[block * 2], chroma[block * 2 + 1]),
+                _ => (chroma[block], chroma[chroma_plane_len + block]),
+            };
+            push_yuv_as_rgb(rgb, luma, i32::from(u) - 128, i32::from(v) - 128);
+        }
+    }
+    Ok(())
+}
+
+// Y plane of 4:2:0 frame is its luminance
+#[cfg(target_os = "linux")]
+fn push_yuv420_luma(
+    luma: &mut Vec<u8>,
+    frame: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<(), WebcamFacialError> {
+    luma.extend_from_slice(yuv420_planes(frame, width, height)?.0);
+    Ok(())
+}
+
+// Grayscale frames from V4L (GREY or little-endian Y16), RGB only for preview
+#[cfg(target_os = "linux")]
+struct GrayscaleSource(rscam::Camera, WebcamFormat, Option<CapturedFrame>);
+
+#[cfg(target_os = "linux")]
+impl GrayscaleSource {
+    // 8-bit luminance of every pixel
+    fn capture_luma(&mut self) -> Result<GrayImage, WebcamFacialError> {
+        let frame = match self.1 {
+            WebcamFormat::Y16 => capture_raw(&self.0, |width, height| width * height * 2)?,
+            _ => capture_raw(&self.0, |width, height| width * height)?,
+        };
+        let (width, height) = frame.resolution;
+        let mut luma = reuse_buffer(&mut self.2, (width * height) as usize);
+        match self.1 {
+            WebcamFormat::Y16 => luma.extend(
+                frame[..(width * height * 2) as usize]
+                    .chunks_exact(2)
+                    .map(|pixel| pixel[1]),
+            ),
+            _ => luma.extend_from_slice(&frame[..(width * height) as usize]),
+        }
+        GrayImage::from_raw(width, height, luma).ok_or_else(|| {
+            WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FrameSource for GrayscaleSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        let luma = self.capture_luma()?;
+        let rgb = RgbImage::from_fn(luma.width(), luma.height(), |x, y| {
+            let value = luma.get_pixel(x, y)[0];
+            image::Rgb([value, value, value])
+        });
+        self.2 = Some(CapturedFrame::Luma(luma));
+        Ok(rgb)
+    }
+
+    fn next_detection_frame(&mut self) -> Result<CapturedFrame, WebcamFacialError> {
+        self.capture_luma().map(CapturedFrame::Luma)
+    }
+
+    fn recycle(&mut self, frame: CapturedFrame) {
+        self.2 = Some(frame);
+    }
+
+    fn stop(&mut self) {
+        if let Err(err) = self.0.stop() {
+            warn!("Error stopping camera: {}", err);
+        }
+    }
+}
+
+// Frames from nokhwa (Windows Media Foundation, macOS AVFoundation), decoded to RGB by nokhwa
+#[cfg(all(feature = "nokhwa", not(target_os = "linux")))]
+struct NokhwaSource(nokhwa::Camera);
+
+#[cfg(all(feature = "nokhwa", not(target_os = "linux")))]
+impl FrameSource for NokhwaSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        let frame = self
+            .0
+            .frame()
+            .map_err(|err| WebcamFacialError::CaptureFailed(err.to_string()))?;
+        let decoded = frame
+            .decode_image::<nokhwa::pixel_format::RgbFormat>()
+            .map_err(|err| WebcamFacialError::CaptureFailed(err.to_string()))?;
+        let (width, height) = decoded.dimensions();
+        RgbImage::from_raw(width, height, decoded.into_raw()).ok_or_else(|| {
+            WebcamFacialError::MalformedFrame("camera returned incomplete frame".to_string())
+        })
+    }
+
+    fn stop(&mut self) {
+        if let Err(err) = self.0.stop_stream() {
+            warn!("Error stopping camera: {}", err);
+        }
+    }
+}
+
+// Capture tasks and threads of one controller still running, so controller can wait
+// until camera is released and tell how long camera capture blocks
+#[derive(Default)]
+pub(crate) struct CaptureActivity {
+    tasks: AtomicU32,
+    threads: AtomicU32,
+    capture_since: Mutex<Option<Instant>>, // Start of capture call in progress
+}
+
+impl CaptureActivity {
+    pub(crate) fn is_idle(&self) -> bool {
+        self.tasks.load(Ordering::SeqCst) == 0 && self.threads.load(Ordering::SeqCst) == 0
+    }
+
+    // How long current capture call waits for camera, None between captures
+    pub(crate) fn capture_blocked(&self) -> Option<Duration> {
+        self.capture_since
+            .lock()
+            .ok()
+            .and_then(|capture_since| *capture_since)
+            .map(|capture_since| capture_since.elapsed())
+    }
+
+    fn set_capture_since(&self, capture_since: Option<Instant>) {
+        if let Ok(mut current) = self.capture_since.lock() {
+            *current = capture_since;
+        }
+    }
+}
+
+// Counts task or capture thread as running until dropped, also when it panics
+pub(crate) struct ActivityGuard(Arc<CaptureActivity>, bool);
+
+impl ActivityGuard {
+    pub(crate) fn task(activity: Arc<CaptureActivity>) -> Self {
+        activity.tasks.fetch_add(1, Ordering::SeqCst);
+        Self(activity, false)
+    }
+
+    fn thread(activity: Arc<CaptureActivity>) -> Self {
+        activity.threads.fetch_add(1, Ordering::SeqCst);
+        Self(activity, true)
+    }
+}
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        let counter = if self.1 {
+            &self.0.threads
+        } else {
+            &self.0.tasks
+        };
+        counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Camera owned by separate capture thread, so waiting for a stalled camera
+// never blocks task shutdown
+pub struct CameraThread {
+    receiver: Receiver<Result<(CapturedFrame, Instant), WebcamFacialError>>,
+    recycle_sender: Sender<CapturedFrame>,
+    running: Arc<AtomicBool>,
+    format: WebcamFormat,
+    framerate: u32,
+}
+
+impl CameraThread {
+    // Without rgb_needed sources may deliver grayscale frames only (FrameSource::next_detection_frame),
+    // skipped malformed frames are counted in dropped_frames
+    pub fn open(
+        source: WebcamFacialSource,
+        settings: CameraSettings,
+        rgb_needed: bool,
+        dropped_frames: Arc<AtomicU32>,
+        activity: Arc<CaptureActivity>,
+    ) -> Result<Self, WebcamFacialError> {
+        let (open_sender, open_receiver) = bounded(1);
+        let (frame_sender, frame_receiver) = bounded(1);
+        let (recycle_sender, recycle_receiver) = bounded::<CapturedFrame>(1);
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        let thread_activity = ActivityGuard::thread(activity);
+        let format = if matches!(source, WebcamFacialSource::Camera) {
+            settings.format
+        } else {
+            WebcamFormat::Rgb
+        };
+        thread::Builder::new()
+            .name("webcam_facial_capture".to_string())
+            .spawn(move || {
+                let activity = &thread_activity.0;
+                let mut frame_source = match open_frame_source(&source, settings) {
+                    Ok((frame_source, framerate)) => {
+                        let _ = open_sender.send(Ok(framerate));
+                        frame_source
+                    }
+                    Err(error) => {
+                        let _ = open_sender.send(Err(error));
+                        return;
+                    }
+                };
+                while thread_running.load(Ordering::SeqCst) {
+                    if let Ok(frame) = recycle_receiver.try_recv() {
+                        frame_source.recycle(frame);
+                    }
+                    // Stamp frame when captured, it may wait in channel for a while
+                    activity.set_capture_since(Some(Instant::now()));
+                    let frame = if rgb_needed {
+                        frame_source.next_frame().map(CapturedFrame::Rgb)
+                    } else {
+                        frame_source.next_detection_frame()
+                    };
+                    activity.set_capture_since(None);
+                    // Single corrupt frame is skipped, camera keeps streaming
+                    if let Err(WebcamFacialError::MalformedFrame(reason)) = &frame {
+                        warn!("Skipping malformed frame: {}", reason);
+                        dropped_frames.fetch_add(1, Ordering::SeqCst);
+                        continue;
+                    }
+                    let frame = frame.map(|frame| (frame, Instant::now()));
+                    let failed = frame.is_err();
+                    // Receiver is gone when task finished
+                    if frame_sender.send(frame).is_err() || failed {
+                        break;
+                    }
+                }
+                frame_source.stop();
+                info!("Camera released.");
+            })
+            .map_err(|err| WebcamFacialError::CameraOpen(err.to_string()))?;
+        let framerate = open_receiver
+            .recv()
+            .map_err(|_| WebcamFacialError::CameraOpen("capture thread failed".to_string()))??;
+        Ok(Self {
+            receiver: frame_receiver,
+            recycle_sender,
+            running,
+            format,
+            framerate,
+        })
+    }
+
+    // Active mode once first frame size is known
+    pub const fn active_config(&self, resolution: (u32, u32)) -> ActiveCameraConfig {
+        ActiveCameraConfig {
+            format: self.format,
+            resolution,
+            framerate: self.framerate,
+        }
+    }
+
+    // Wait for next frame and its capture time, returns None if task_running was cleared while waiting
+    pub fn next_frame(
+        &self,
+        timeout: Duration,
+        task_running: &AtomicBool,
+    ) -> Result<Option<(RecycledFrame, Instant)>, WebcamFacialError> {
+        let started = Instant::now();
+        loop {
+            match self.receiver.recv_timeout(STOP_POLL_INTERVAL) {
+                Ok(frame) => {
+                    return frame.map(|(frame, captured)| {
+                        let frame = RecycledFrame {
+                            frame: Some(frame),
+                            recycle_sender: self.recycle_sender.clone(),
+                        };
+                        Some((frame, captured))
+                    })
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if !task_running.load(Ordering::SeqCst) {
+                        return Ok(None);
+                    }
+                    if started.elapsed() >= timeout {
+                        return Err(WebcamFacialError::CaptureFailed(format!(
+                            "no frame received in {:?}",
+                            timeout
+                        )));
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(WebcamFacialError::CaptureFailed(
+                        "capture thread stopped".to_string(),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for CameraThread {
+    fn drop(&mut self) {
+        // Capture thread releases camera after its current capture returns
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+// Check requested resolutions against modes camera reports, use first supported one,
+// nearest supported mode or report available modes
+#[cfg(target_os = "linux")]
+fn resolve_webcam_mode(
+    camera_device: u32,
+    resolutions: &[(u32, u32)],
+    camera_framerate: u32,
+    camera_format: WebcamFormat,
+    nearest_mode: bool,
+) -> Result<(u32, u32, u32), WebcamFacialError> {
+    let Some(&(camera_width, camera_height)) = resolutions.first() else {
+        return Err(WebcamFacialError::UnsupportedMode(
+            "no resolution requested".to_string(),
+            Vec::new(),
+        ));
+    };
+    // Let driver decide if camera can't be queried, opening it reports the error
+    let Some(device_info) = query_webcam_device(camera_device) else {
+        return Ok((camera_width, camera_height, camera_framerate));
+    };
+    // libv4l converts any native format to RGB, other formats are streamed as is
+    // is_none_or needs Rust 1.82
+    #[allow(clippy::unnecessary_map_or)]
+    let modes: Vec<WebcamMode> = device_info
+        .modes
+        .into_iter()
+        .filter(|mode| {
+            camera_format
+                .fourcc()
+                .map_or(true, |fourcc| mode.format.as_bytes() == fourcc)
+        })
+        .collect();
+    if modes.is_empty() {
+        return Ok((camera_width, camera_height, camera_framerate));
+    }
+    let supports_framerate = |mode: &WebcamMode, framerate: u32| {
+        mode.framerates.is_empty()
+            || mode
+                .framerates
+                .iter()
+                .any(|rate| (rate - f64::from(framerate)).abs() < 0.5)
+    };
+    // Requested framerate or closest one mode supports
+    let mode_framerate = |mode: &WebcamMode| {
+        if supports_framerate(mode, camera_framerate) {
+            camera_framerate
+        } else {
+            mode.framerates
+                .iter()
+                .min_by(|a, b| {
+                    (*a - f64::from(camera_framerate))
+                        .abs()
+                        .total_cmp(&(*b - f64::from(camera_framerate)).abs())
+                })
+                .map_or(camera_framerate, |rate| rate.round() as u32)
+        }
+    };
+    // First resolution supported with requested framerate
+    if let Some(&resolution) = resolutions.iter().find(|(width, height)| {
+        modes.iter().any(|mode| {
+            mode.width == *width
+                && mode.height == *height
+                && supports_framerate(mode, camera_framerate)
+        })
+    }) {
+        if resolution != (camera_width, camera_height) {
+            info!(
+                "Using fallback resolution {}x{}.",
+                resolution.0, resolution.1
+            );
+        }
+        return Ok((resolution.0, resolution.1, camera_framerate));
+    }
+    let requested = resolutions
+        .iter()
+        .map(|(width, height)| format!("{}x{}", width, height))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let requested = format!("{} at {} fps", requested, camera_framerate);
+    if !nearest_mode {
+        return Err(WebcamFacialError::UnsupportedMode(requested, modes));
+    }
+    let distance = |mode: &WebcamMode| {
+        let width_diff = i64::from(mode.width) - i64::from(camera_width);
+        let height_diff = i64::from(mode.height) - i64::from(camera_height);
+        width_diff * width_diff + height_diff * height_diff
+    };
+    // Prefer resolution nearest to first requested one with requested framerate, then nearest at all
+    let nearest = modes
+        .iter()
+        .filter(|mode| supports_framerate(mode, camera_framerate))
+        .min_by_key(|mode| distance(mode))
+        .or_else(|| modes.iter().min_by_key(|mode| distance(mode)))
+        .ok_or_else(|| WebcamFacialError::UnsupportedMode(requested.clone(), modes.clone()))?;
+    let framerate = mode_framerate(nearest);
+    warn!(
+        "Camera doesn't support {}, using nearest {}x{} at {} fps.",
+        requested, nearest.width, nearest.height, framerate
+    );
+    Ok((nearest.width, nearest.height, framerate))
+}
+
+// Stream camera native format (MJPEG, YUYV) without libv4l conversion
+#[cfg(target_os = "linux")]
+fn start_v4l_camera(
+    camera_device: u32,
+    camera_width: u32,
+    camera_height: u32,
+    camera_framerate: u32,
+    fourcc: &'static [u8; 4],
+) -> Result<rscam::Camera, WebcamFacialError> {
+    let format_name = String::from_utf8_lossy(fourcc);
+    let device_path = format!("/dev/video{}", camera_device);
+    let mut camera = rscam::Camera::new(&device_path).map_err(|err| {
+        WebcamFacialError::CameraOpen(format!("device [{}]: {}", camera_device, err))
+    })?;
+    info!("Using '{}' camera.", device_path);
+    // Check if camera can stream format at all
+    let formats: Vec<[u8; 4]> = camera
+        .formats()
+        .filter_map(Result::ok)
+        .map(|info| info.format)
+        .collect();
+    if !formats.contains(fourcc) {
+        let available: Vec<String> = formats
+            .iter()
+            .map(|format| String::from_utf8_lossy(format).into_owned())
+            .collect();
+        return Err(WebcamFacialError::CameraStart(format!(
+            "{} format not supported by camera, available formats: {}",
+            format_name,
+            available.join(", ")
+        )));
+    }
+    camera
+        .start(&rscam::Config {
+            interval: (1, camera_framerate),
+            resolution: (camera_width, camera_height),
+            format: fourcc,
+            ..Default::default()
+        })
+        .map_err(|err| {
+            WebcamFacialError::CameraStart(format!(
+                "{} {}x{} at {} fps: {}",
+                format_name, camera_width, camera_height, camera_framerate, err
+            ))
+        })?;
+    info!(
+        "Camera {} stream started at {}x{}, {} fps.",
+        format_name, camera_width, camera_height, camera_framerate
+    );
+    Ok(camera)
+}
+
+#[cfg(any(target_os = "linux", all(windows, not(feature = "nokhwa"))))]
+fn get_camera_frame_iterator(
+    camera_device: u32,
+    camera_width: u32,
+    camera_height: u32,
+    camera_framerate: u32,
+) -> Result<camera_capture::ImageIterator, WebcamFacialError> {
+    // Create the camera device
+    let camera_device = match camera_capture::create(camera_device) {
+        Ok(device) => {
+            #[cfg(target_os = "linux")]
+            info!("Using '/dev/video{}' camera.", camera_device);
+            #[cfg(windows)]
+            info!("Using camera ID:{}.", camera_device);
+            device
+        }
+        Err(err) => {
+            return Err(WebcamFacialError::CameraOpen(format!(
+                "device [{}]: {}",
+                camera_device, err
+            )));
+        }
+    };
+    // Set the resolution
+    let resolution_device = match camera_device.resolution(camera_width, camera_height) {
+        Ok(resolution) => {
+            info!(
+                "Camera resolution set to {}x{}.",
+                camera_width, camera_height
+            );
+            resolution
+        }
+        Err(err) => {
+            return Err(WebcamFacialError::CameraStart(format!(
+                "resolution {}x{}: {:?}",
+                camera_width, camera_height, err
+            )));
+        }
+    };
+    // Set the frame rate and start the camera capture
+    let cam_iter = match resolution_device.fps(f64::from(camera_framerate)) {
+        Ok(fps) => {
+            info!("Camera fps set to {}.", camera_framerate);
+            fps.start()
+        }
+        Err(err) => {
+            return Err(WebcamFacialError::CameraStart(format!(
+                "frame rate {}: {:?}",
+                camera_framerate, err
+            )));
+        }
+    };
+    cam_iter.map_err(|err| WebcamFacialError::CameraStart(err.to_string()))
+}
+
+#[cfg(all(feature = "nokhwa", not(target_os = "linux")))]
+fn open_nokhwa_camera(
+    camera_device: u32,
+    camera_width: u32,
+    camera_height: u32,
+    camera_framerate: u32,
+    camera_format: WebcamFormat,
+) -> Result<NokhwaSource, WebcamFacialError> {
+    use nokhwa::{
+        pixel_format::RgbFormat,
+        utils::{
+            CameraFormat, CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType,
+            Resolution,
+        },
+    };
+
+    // macOS asks user for camera access, first open fails until it's granted
+    #[cfg(target_os = "macos")]
+    nokhwa::nokhwa_initialize(|granted| {
+        if !granted {
+            warn!("Camera access denied.");
+        }
+    });
+    let frame_format = match camera_format {
+        WebcamFormat::Rgb | WebcamFormat::Yuyv => FrameFormat::YUYV,
+        WebcamFormat::Mjpeg => FrameFormat::MJPEG,
+        // nokhwa has no YU12, closest request is the other 4:2:0 format
+        WebcamFormat::Nv12 | WebcamFormat::Yu12 => FrameFormat::NV12,
+        WebcamFormat::Grey | WebcamFormat::Y16 => FrameFormat::GRAY,
+    };
+    let requested_format =
+        RequestedFormat::new::<RgbFormat>(RequestedFormatType::Closest(CameraFormat::new(
+            Resolution::new(camera_width, camera_height),
+            frame_format,
+            camera_framerate,
+        )));
+    let mut camera = nokhwa::Camera::new(CameraIndex::Index(camera_device), requested_format)
+        .map_err(|err| {
+            WebcamFacialError::CameraOpen(format!("device [{}]: {}", camera_device, err))
+        })?;
+    info!("Using camera ID:{}.", camera_device);
+    camera.open_stream().map_err(|err| {
+        WebcamFacialError::CameraStart(format!(
+            "{}x{} at {} fps: {}",
+            camera_width, camera_height, camera_framerate, err
+        ))
+    })?;
+    // Closest mode is used when requested one isn't supported
+    let camera_format = camera.camera_format();
+    info!(
+        "Camera stream started at {}x{}, {} fps.",
+        camera_format.width(),
+        camera_format.height(),
+        camera_format.frame_rate()
+    );
+    Ok(NokhwaSource(camera))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    // BT.601 colors as Y, U, V
+    const BLACK: [u8; 3] = [16, 128, 128];
+    const WHITE: [u8; 3] = [235, 128, 128];
+    const RED: [u8; 3] = [81, 90, 240];
+    const BLUE: [u8; 3] = [41, 240, 110];
+    const GREEN: [u8; 3] = [145, 54, 34];
+
+    fn yuv420_rgb(frame: &[u8], width: u32, height: u32, format: WebcamFormat) -> Vec<u8> {
+        let mut rgb = Vec::new();
+        push_yuv420_as_rgb(&mut rgb, frame, width, height, format).unwrap();
+        rgb
+    }
+
+    #[test]
+    fn yuv_extremes_saturate() {
+        let rgb = |luma, u, v| {
+            let mut rgb = Vec::new();
+            push_yuv_as_rgb(&mut rgb, luma, u, v);
+            rgb
+        };
+        assert_eq!(rgb(255, 0, 127)[0], 255);
+        assert_eq!(rgb(0, 0, -128)[0], 0);
+        assert_eq!(rgb(255, -128, -128)[1], 255);
+        assert_eq!(rgb(0, 127, 127)[1], 0);
+        assert_eq!(rgb(255, 127, 0)[2], 255);
+        assert_eq!(rgb(0, -128, 0)[2], 0);
+    }
+
+    #[test]
+    fn yuyv_1x1_half_pair() {
+        let mut rgb = Vec::new();
+        push_yuyv_as_rgb(&mut rgb, &[WHITE[0], WHITE[1]]);
+        assert_eq!(rgb, [255, 255, 255]);
+    }
+
+    #[test]
+    fn yuyv_3x1_reuses_last_chroma() {
+        let mut rgb = Vec::new();
+        push_yuyv_as_rgb(
+            &mut rgb,
+            &[RED[0], RED[1], RED[0], RED[2], BLUE[0], BLUE[1]],
+        );
+        // Last pixel has own U and red V
+        assert_eq!(rgb, [255, 0, 0, 255, 0, 0, 208, 0, 255]);
+    }
+
+    #[test]
+    fn yuv420_2x2_shares_chroma() {
+        let luma = [BLACK[0], WHITE[0], WHITE[0], BLACK[0]];
+        let expected = [0, 0, 0, 255, 255, 255, 255, 255, 255, 0, 0, 0];
+        let nv12 = [&luma[..], &[128, 128]].concat();
+        assert_eq!(yuv420_rgb(&nv12, 2, 2, WebcamFormat::Nv12), expected);
+        assert_eq!(yuv420_rgb(&nv12, 2, 2, WebcamFormat::Yu12), expected);
+    }
+
+    #[test]
+    fn yuv420_3x3_maps_chroma_blocks() {
+        // 2x2 chroma blocks: red, blue in last column, green in last row, black in corner
+        let luma = [
+            RED[0], RED[0], BLUE[0], //
+            RED[0], RED[0], BLUE[0], //
+            GREEN[0], GREEN[0], BLACK[0],
+        ];
+        let nv12 = [
+            &luma[..],
+            &[
+                RED[1], RED[2], BLUE[1], BLUE[2], GREEN[1], GREEN[2], BLACK[1], BLACK[2],
+            ],
+        ]
+        .concat();
+        let yu12 = [
+            &luma[..],
+            &[RED[1], BLUE[1], GREEN[1], BLACK[1]],
+            &[RED[2], BLUE[2], GREEN[2], BLACK[2]],
+        ]
+        .concat();
+        let (red, blue, green, black) = ([255, 0, 0], [0, 0, 255], [0, 255, 1], [0, 0, 0]);
+        let expected = [red, red, blue, red, red, blue, green, green, black].concat();
+        assert_eq!(yuv420_rgb(&nv12, 3, 3, WebcamFormat::Nv12), expected);
+        assert_eq!(yuv420_rgb(&yu12, 3, 3, WebcamFormat::Yu12), expected);
+    }
+
+    #[test]
+    fn yuv420_luma_is_y_plane() {
+        let frame = [
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 128, 128, 128, 128, 128, 128, 128, 128,
+        ];
+        let mut luma = Vec::new();
+        push_yuv420_luma(&mut luma, &frame, 3, 3).unwrap();
+        assert_eq!(luma, frame[..9]);
+    }
+
+    #[test]
+    fn yuv420_malformed_frame() {
+        let mut rgb = Vec::new();
+        assert!(matches!(
+            push_yuv420_as_rgb(&mut rgb, &[16; 6], 0, 2, WebcamFormat::Nv12),
+            Err(WebcamFacialError::MalformedFrame(_))
+        ));
+        assert!(matches!(
+            push_yuv420_as_rgb(&mut rgb, &[16; 5], 2, 2, WebcamFormat::Yu12),
+            Err(WebcamFacialError::MalformedFrame(_))
+        ));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::WebcamFacialData;
+
+// Origin and axis directions of position fields (config_coordinate_mode)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoordinateMode {
+    // Image convention, x right, y down, x in 0.0 .. 100.0 and y in same units (0.0 .. 75.0 for 4:3),
+    // normalized in 0.0 .. 1.0
+    TopLeftOrigin,
+    #[default]
+    CenterOriginYDown, // Frame center, x flipped (positive - left of frame), y down, normalized in -1.0 .. 1.0
+    CenterOriginYUp, // Same as CenterOriginYDown with y up, matches Bevy world coordinates
+}
+
+impl CoordinateMode {
+    // Convert data calculated in CenterOriginYDown, sizes, angles and ids are kept
+    pub(crate) fn apply(self, data: WebcamFacialData) -> WebcamFacialData {
+        if !data.face_found {
+            return data;
+        }
+        match self {
+            Self::CenterOriginYDown => data,
+            Self::CenterOriginYUp => WebcamFacialData {
+                center_y: -data.center_y,
+                y: -(data.y + data.height), // Rectangle corner with lowest y stays x,y
+                normalized_y: -data.normalized_y,
+                velocity_y: -data.velocity_y,
+                ..data
+            },
+            Self::TopLeftOrigin => WebcamFacialData {
+                center_x: 50.0 - data.center_x,
+                center_y: data.center_y + 50.0,
+                x: data.x + 50.0,
+                y: data.y + 50.0,
+                normalized_x: (1.0 - data.normalized_x) / 2.0,
+                normalized_y: (1.0 + data.normalized_y) / 2.0,
+                velocity_x: -data.velocity_x / 2.0,
+                velocity_y: data.velocity_y / 2.0,
+                ..data
+            },
+        }
+    }
+
+    // Calibration offset of output coordinates converted to CenterOriginYDown,
+    // task subtracts it there before deadzone so deadzone is centered on neutral position
+    pub(crate) fn calibration_offset(
+        self,
+        (center_x, center_y, normalized_x, normalized_y): (f32, f32, f32, f32),
+    ) -> (f32, f32, f32, f32) {
+        match self {
+            Self::CenterOriginYDown => (center_x, center_y, normalized_x, normalized_y),
+            Self::CenterOriginYUp => (center_x, -center_y, normalized_x, -normalized_y),
+            Self::TopLeftOrigin => (-center_x, center_y, -2.0 * normalized_x, 2.0 * normalized_y),
+        }
+    }
+
+    // Face box center as normalized_x/y of CenterOriginYDown, taken from x, y, width and height
+    // which aren't scaled by sensitivity or moved by calibration and deadzone like normalized_x/y
+    #[cfg(feature = "bevy")]
+    pub(crate) fn box_center(self, data: &WebcamFacialData) -> (f32, f32) {
+        // Box corner from frame left/top, both in percent of frame width
+        let (left, top) = match self {
+            Self::CenterOriginYDown => (data.x + 50.0, data.y + 50.0),
+            Self::CenterOriginYUp => (data.x + 50.0, 50.0 - data.y - data.height),
+            Self::TopLeftOrigin => (data.x, data.y),
+        };
+        // Normalized height is relative to frame height, so it converts vertical units
+        let vertical_scale = data.normalized_height / data.height.max(f32::EPSILON);
+        (
+            1.0 - 2.0 * data.normalized_width.mul_add(0.5, left / 100.0),
+            2.0f32.mul_add(
+                data.normalized_height.mul_add(0.5, top * vertical_scale),
+                -1.0,
+            ),
+        )
+    }
+
+    // Face center in window logical pixels with origin top-left (like Window::cursor_position()),
+    // face moving to user's right moves right in window like in a mirror
+    #[cfg(feature = "bevy")]
+    pub(crate) fn window_position(
+        self,
+        data: &WebcamFacialData,
+        (window_width, window_height): (f32, f32),
+    ) -> (f32, f32) {
+        let (center_x, center_y) = self.box_center(data);
+        (
+            (1.0 + center_x) * window_width / 2.0,
+            (1.0 + center_y) * window_height / 2.0,
+        )
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::{
+    ecs::{
+        event::EventReader,
+        query::With,
+        system::{Query, ResMut, Resource},
+    },
+    math::Vec2,
+    window::{PrimaryWindow, Window},
+};
+
+use crate::WebcamFacialDataEvent;
+
+// Virtual cursor driven by smoothed face center of one camera (only with 'cursor' feature).
+// Position is in primary window logical pixels with origin top-left, like Window::cursor_position().
+// Settings can be changed at runtime, expects default CoordinateMode::CenterOriginYDown
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct WebcamFacialCursor {
+    pub position: Vec2,
+    pub face_found: bool, // false - position is held where face was last seen
+    pub sensitivity: f32, // 1.0 - face at frame edge moves cursor to window edge, higher - less head movement
+    pub invert_x: bool,
+    pub invert_y: bool,
+    pub camera_id: u32, // Camera which data drives cursor
+}
+
+// Map face data events to cursor position within window bounds
+pub fn webcam_facial_cursor_updater(
+    mut cursor: ResMut<WebcamFacialCursor>,
+    mut data_events: EventReader<WebcamFacialDataEvent>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Some(data) = data_events
+        .read()
+        .map(|event| event.0)
+        .filter(|data| data.camera_id == cursor.camera_id)
+        .last()
+    else {
+        return;
+    };
+    cursor.face_found = data.face_found;
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    if !data.face_found {
+        return;
+    }
+    // normalized_x is flipped (positive - user's right), both axes -1.0 .. 1.0 from frame center
+    let size = Vec2::new(window.width(), window.height());
+    let direction = Vec2::new(
+        if cursor.invert_x { -1.0 } else { 1.0 },
+        if cursor.invert_y { -1.0 } else { 1.0 },
+    );
+    let offset = Vec2::new(data.normalized_x, data.normalized_y) * direction * cursor.sensitivity;
+    cursor.position = (size / 2.0 + offset * size / 2.0).clamp(Vec2::ZERO, size);
+}
@@ -0,0 +1,254 @@
+use image::GrayImage;
+use log::info;
+use std::{path::Path, sync::Arc};
+
+use crate::WebcamFacialError;
+
+// Face rectangle in frame pixels found by FaceDetector
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DetectedFace {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub score: f64, // Higher - more likely a true face
+}
+
+// Face detection algorithm used by capture task, rustface SeetaFace model by default
+pub trait FaceDetector {
+    fn detect(&mut self, image: &GrayImage) -> Vec<DetectedFace>;
+
+    // Thresholds changed at runtime through controller, ignored by default
+    fn set_thresholds(&mut self, _min_face_size: u32, _score_thresh: f64) {}
+
+    // Image pyramid scale and sliding window step changed at runtime by preset, ignored by default
+    fn set_search(&mut self, _pyramid_scale: f32, _slide_window: (u32, u32)) {}
+}
+
+// Detector settings bundled by DetectorPreset
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DetectorParams {
+    pub min_face_size: u32,
+    pub score_thresh: f64,
+    pub pyramid_scale: f32,
+    pub slide_window: (u32, u32),
+}
+
+// Quality/performance dial for detector settings, switched at runtime by controller set_preset().
+// Balanced has plugin default values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DetectorPreset {
+    Fast, // Close faces only, coarse search
+    #[default]
+    Balanced,
+    Accurate, // Weaker and smaller faces, dense search, slowest
+}
+
+impl DetectorPreset {
+    pub const ALL: [Self; 3] = [Self::Fast, Self::Balanced, Self::Accurate];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Fast => "fast",
+            Self::Balanced => "balanced",
+            Self::Accurate => "accurate",
+        }
+    }
+
+    pub const fn params(self) -> DetectorParams {
+        match self {
+            Self::Fast => DetectorParams {
+                min_face_size: 60,
+                score_thresh: 2.0,
+                pyramid_scale: 0.7,
+                slide_window: (8, 8),
+            },
+            Self::Balanced => DetectorParams {
+                min_face_size: 20,
+                score_thresh: 2.0,
+                pyramid_scale: 0.8,
+                slide_window: (4, 4),
+            },
+            Self::Accurate => DetectorParams {
+                min_face_size: 20,
+                score_thresh: 1.5,
+                pyramid_scale: 0.9,
+                slide_window: (2, 2),
+            },
+        }
+    }
+}
+
+// Creates detector inside capture task each time capture starts (config_face_detector)
+pub type FaceDetectorFactory =
+    Arc<dyn Fn() -> Result<Box<dyn FaceDetector>, WebcamFacialError> + Send + Sync>;
+
+// rustface doesn't search smaller faces
+const RUSTFACE_MIN_FACE_SIZE: u32 = 20;
+
+// model_checksum() of SeetaFace frontal face model shipped in assets/NN_Models/seeta.bin
+pub const SEETA_MODEL_CHECKSUM: u64 = 0xa73c_f041_164d_43cb;
+
+// FNV-1a hash of model bytes, tells wrong or corrupt model file apart (not cryptographic)
+pub fn model_checksum(model: &[u8]) -> u64 {
+    model.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// Model with other checksum is reported as WebcamFacialError::ModelChecksum
+pub fn validate_model(model: &[u8], expected: u64) -> Result<(), WebcamFacialError> {
+    let found = model_checksum(model);
+    if found == expected {
+        Ok(())
+    } else {
+        Err(WebcamFacialError::ModelChecksum(expected, found))
+    }
+}
+
+// SeetaFace model of default detector (config_model_path, config_model_bytes, config_model_checksum)
+#[derive(Clone, Debug)]
+pub(crate) struct DetectorModel {
+    pub path: String,
+    pub bytes: Option<&'static [u8]>,
+    pub checksum: Option<u64>,
+}
+
+impl DetectorModel {
+    // Model from memory or file, validated before rustface parses it when checksum is set
+    pub fn load(
+        &self,
+        min_face_size: u32,
+        score_thresh: f64,
+        pyramid_scale: f32,
+        slide_window: (u32, u32),
+    ) -> Result<RustfaceDetector, WebcamFacialError> {
+        let file_bytes;
+        let model = match (self.bytes, self.checksum) {
+            (Some(bytes), _) => {
+                info!("Using recognition model from memory.");
+                bytes
+            }
+            (None, None) => {
+                return RustfaceDetector::new(
+                    &self.path,
+                    min_face_size,
+                    score_thresh,
+                    pyramid_scale,
+                    slide_window,
+                )
+            }
+            (None, Some(_)) => {
+                file_bytes = std::fs::read(&self.path).map_err(|error| {
+                    WebcamFacialError::DetectorLoad(format!(
+                        "model file '{}': {}",
+                        self.path, error
+                    ))
+                })?;
+                info!("Using {} recognition model.", self.path);
+                &file_bytes
+            }
+        };
+        if let Some(checksum) = self.checksum {
+            validate_model(model, checksum)?;
+        }
+        RustfaceDetector::from_bytes(
+            model,
+            min_face_size,
+            score_thresh,
+            pyramid_scale,
+            slide_window,
+        )
+    }
+}
+
+// Default detector using rustface with SeetaFace model
+pub struct RustfaceDetector(Box<dyn rustface::Detector>);
+
+impl RustfaceDetector {
+    pub fn new(
+        model_path: &str,
+        min_face_size: u32,
+        score_thresh: f64,
+        pyramid_scale: f32,
+        (slide_step_x, slide_step_y): (u32, u32),
+    ) -> Result<Self, WebcamFacialError> {
+        if !Path::new(model_path).is_file() {
+            return Err(WebcamFacialError::DetectorLoad(format!(
+                "model file '{}' not found",
+                model_path
+            )));
+        }
+        let detector = rustface::create_detector(model_path)
+            .map_err(|error| WebcamFacialError::DetectorLoad(error.to_string()))?;
+        info!("Using {} recognition model.", model_path);
+        Ok(Self::configured(
+            detector,
+            min_face_size,
+            score_thresh,
+            pyramid_scale,
+            (slide_step_x, slide_step_y),
+        ))
+    }
+
+    // Model loaded from memory, ex. bundled into binary with include_bytes!
+    pub fn from_bytes(
+        model: &[u8],
+        min_face_size: u32,
+        score_thresh: f64,
+        pyramid_scale: f32,
+        slide_window: (u32, u32),
+    ) -> Result<Self, WebcamFacialError> {
+        let model = rustface::read_model(model)
+            .map_err(|error| WebcamFacialError::DetectorLoad(error.to_string()))?;
+        Ok(Self::configured(
+            rustface::create_detector_with_model(model),
+            min_face_size,
+            score_thresh,
+            pyramid_scale,
+            slide_window,
+        ))
+    }
+
+    fn configured(
+        mut detector: Box<dyn rustface::Detector>,
+        min_face_size: u32,
+        score_thresh: f64,
+        pyramid_scale: f32,
+        (slide_step_x, slide_step_y): (u32, u32),
+    ) -> Self {
+        detector.set_min_face_size(min_face_size.max(RUSTFACE_MIN_FACE_SIZE));
+        detector.set_score_thresh(score_thresh);
+        detector.set_pyramid_scale_factor(pyramid_scale);
+        detector.set_slide_window_step(slide_step_x, slide_step_y);
+        Self(detector)
+    }
+}
+
+impl FaceDetector for RustfaceDetector {
+    fn detect(&mut self, image: &GrayImage) -> Vec<DetectedFace> {
+        let (width, height) = image.dimensions();
+        self.0
+            .detect(&rustface::ImageData::new(image, width, height))
+            .iter()
+            .map(|face| DetectedFace {
+                x: face.bbox().x(),
+                y: face.bbox().y(),
+                width: face.bbox().width(),
+                height: face.bbox().height(),
+                score: face.score(),
+            })
+            .collect()
+    }
+
+    fn set_thresholds(&mut self, min_face_size: u32, score_thresh: f64) {
+        self.0
+            .set_min_face_size(min_face_size.max(RUSTFACE_MIN_FACE_SIZE));
+        self.0.set_score_thresh(score_thresh);
+    }
+
+    fn set_search(&mut self, pyramid_scale: f32, (slide_step_x, slide_step_y): (u32, u32)) {
+        self.0.set_pyramid_scale_factor(pyramid_scale);
+        self.0.set_slide_window_step(slide_step_x, slide_step_y);
+    }
+}
@@ -0,0 +1,1656 @@
+use crossbeam_channel::{bounded, unbounded, Receiver, SendError, Sender};
+use log::{debug, error, info, trace, warn};
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+#[cfg(target_os = "linux")]
+use crate::list_webcam_devices;
+use crate::{
+    apply_deadzone, apply_sensitivity,
+    camera::{
+        set_webcam_control, webcam_controls, ActiveCameraConfig, ActivityGuard, CameraSettings,
+        CameraThread, CaptureActivity,
+    },
+    clamp_roi, create_detector, detect_faces,
+    detector::DetectorModel,
+    facial_data_from_face, resolve_webcam_device_id, send_latest, sleep_while_running,
+    slide_window_bits, slide_window_from_bits, subtract_calibration, CoordinateMode, DetectedFace,
+    DetectorParams, DetectorPreset, FaceDetectorFactory, FaceSelection, FrameRotation,
+    SmoothingFilterType, WebcamControl, WebcamFacialData, WebcamFacialDataFiltered,
+    WebcamFacialDataGrace, WebcamFacialDataHold, WebcamFacialDataSmoothed,
+    WebcamFacialDataVelocity, WebcamFacialDiagnostics, WebcamFacialError, WebcamFacialSource,
+    WebcamFacialStats, WebcamFacialStatsWindow, WebcamFacialTracker, WebcamFormat,
+    REFERENCE_FACE_WIDTH_CM, SEETA_MODEL_CHECKSUM, STOP_POLL_INTERVAL, WEBCAM_HORIZONTAL_FOV,
+};
+#[cfg(feature = "blink")]
+use crate::{blink, WebcamFacialEyes};
+#[cfg(feature = "network")]
+use crate::{network, NetworkFormat};
+#[cfg(feature = "frame_preview")]
+use crate::{preview, CapturedFrame};
+
+// Capture and detection settings of WebcamFacialEngine, same config_* fields as WebcamFacialPlugin
+// without the ones only Bevy app uses (autostart, events, window, cursor)
+#[derive(Clone)]
+pub struct WebcamFacialConfig {
+    pub config_camera_id: u32,
+    pub config_frame_source: WebcamFacialSource,
+    pub config_webcam_device: u32,
+    pub config_webcam_device_id: Option<String>, // Resolved to device number every time capture starts
+    pub config_webcam_width: u32,
+    pub config_webcam_height: u32,
+    pub config_webcam_resolutions: Vec<(u32, u32)>,
+    pub config_webcam_framerate: u32,
+    pub config_webcam_format: WebcamFormat,
+    pub config_webcam_timeout: Duration,
+    pub config_webcam_reconnect_interval: Duration,
+    pub config_warmup_frames: u32,
+    pub config_webcam_nearest_mode: bool,
+    pub config_flip_horizontal: bool,
+    pub config_flip_vertical: bool,
+    pub config_rotation: FrameRotation,
+    pub config_webcam_fov: f32,
+    pub config_pixel_aspect: f32,
+    pub config_reference_face_width_cm: f32,
+    pub config_detection_interval: u32,
+    pub config_min_frame_interval: Duration,
+    pub config_channel_capacity: usize,
+    pub config_max_staleness: Duration,
+    #[cfg(feature = "network")]
+    pub config_network_address: Option<String>,
+    #[cfg(feature = "network")]
+    pub config_network_format: NetworkFormat,
+    #[cfg(feature = "frame_preview")]
+    pub config_expose_frame: bool,
+    #[cfg(feature = "frame_preview")]
+    pub config_overlay_color: Option<[u8; 4]>, // RGBA
+    #[cfg(feature = "frame_preview")]
+    pub config_overlay_thickness: u32,
+    #[cfg(feature = "frame_preview")]
+    pub config_preview_mirror: bool,
+    pub config_filter_type: SmoothingFilterType,
+    pub config_filter_length: u32,
+    pub config_smoothing: f32,
+    pub config_size_smoothing: Option<f32>,
+    pub config_deadzone_x: f32,
+    pub config_deadzone_y: f32,
+    pub config_sensitivity_x: f32,
+    pub config_sensitivity_y: f32,
+    pub config_coordinate_mode: CoordinateMode,
+    pub config_tracking_distance: f32,
+    pub config_detection_scale: f32,
+    pub config_roi: Option<(u32, u32, u32, u32)>,
+    pub config_min_face_size: u32,
+    pub config_score_thresh: f64,
+    pub config_min_emit_score: f32,
+    pub config_acquire_score: f32,
+    pub config_keep_score: f32,
+    pub config_min_track_box_area: u32,
+    pub config_diagnostics: bool, // All detector candidates of every frame, poll_diagnostics()
+    pub config_max_faces: usize,
+    pub config_face_selection: FaceSelection,
+    pub config_lost_grace_frames: u32,
+    pub config_hold_last_on_dropout: bool,
+    pub config_hold_max_duration: Duration,
+    #[cfg(feature = "blink")]
+    pub config_blink_threshold: f32,
+    pub config_pyramid_scale: f32,
+    pub config_slide_window: (u32, u32),
+    pub config_model_path: String,
+    pub config_model_bytes: Option<&'static [u8]>,
+    pub config_model_checksum: Option<u64>,
+    pub config_face_detector: Option<FaceDetectorFactory>,
+}
+
+impl Default for WebcamFacialConfig {
+    fn default() -> Self {
+        Self {
+            config_camera_id: 0,
+            config_frame_source: WebcamFacialSource::Camera,
+            config_webcam_device: 0,
+            config_webcam_device_id: None,
+            config_webcam_width: 640,
+            config_webcam_height: 480,
+            config_webcam_resolutions: Vec::new(),
+            config_webcam_framerate: 15,
+            config_webcam_format: WebcamFormat::Rgb,
+            config_webcam_timeout: Duration::from_secs(5),
+            config_webcam_reconnect_interval: Duration::from_secs(2),
+            config_warmup_frames: 5,
+            config_webcam_nearest_mode: true,
+            config_flip_horizontal: false,
+            config_flip_vertical: false,
+            config_rotation: FrameRotation::None,
+            config_webcam_fov: WEBCAM_HORIZONTAL_FOV,
+            config_pixel_aspect: 1.0,
+            config_reference_face_width_cm: REFERENCE_FACE_WIDTH_CM,
+            config_detection_interval: 1,
+            config_min_frame_interval: Duration::ZERO,
+            config_channel_capacity: 1,
+            config_max_staleness: Duration::ZERO,
+            #[cfg(feature = "network")]
+            config_network_address: None,
+            #[cfg(feature = "network")]
+            config_network_format: NetworkFormat::Json,
+            #[cfg(feature = "frame_preview")]
+            config_expose_frame: false,
+            #[cfg(feature = "frame_preview")]
+            config_overlay_color: None,
+            #[cfg(feature = "frame_preview")]
+            config_overlay_thickness: 2,
+            #[cfg(feature = "frame_preview")]
+            config_preview_mirror: false,
+            config_filter_type: SmoothingFilterType::LowPass(0.1),
+            config_filter_length: 10,
+            config_smoothing: 0.0,
+            config_size_smoothing: None,
+            config_deadzone_x: 0.0,
+            config_deadzone_y: 0.0,
+            config_sensitivity_x: 1.0,
+            config_sensitivity_y: 1.0,
+            config_coordinate_mode: CoordinateMode::CenterOriginYDown,
+            config_tracking_distance: 0.2,
+            config_detection_scale: 1.0,
+            config_roi: None,
+            config_min_face_size: 20,
+            config_score_thresh: 2.0,
+            config_min_emit_score: 0.0,
+            config_acquire_score: 0.0,
+            config_keep_score: 0.0,
+            config_min_track_box_area: 0,
+            config_diagnostics: false,
+            config_max_faces: 0,
+            config_face_selection: FaceSelection::HighestScore,
+            config_lost_grace_frames: 0,
+            config_hold_last_on_dropout: false,
+            config_hold_max_duration: Duration::from_secs(1),
+            #[cfg(feature = "blink")]
+            config_blink_threshold: 0.6,
+            config_pyramid_scale: 0.8,
+            config_slide_window: (4, 4),
+            config_model_path: "assets/NN_Models/seeta.bin".to_string(),
+            config_model_bytes: None,
+            config_model_checksum: Some(SEETA_MODEL_CHECKSUM),
+            config_face_detector: None,
+        }
+    }
+}
+
+// Builder on top of default settings, ex. WebcamFacialConfig::default().with_device(1).with_resolution(1280, 720)
+impl WebcamFacialConfig {
+    pub fn with_camera_id(mut self, camera_id: u32) -> Self {
+        self.config_camera_id = camera_id;
+        self
+    }
+
+    pub fn with_frame_source(mut self, frame_source: WebcamFacialSource) -> Self {
+        self.config_frame_source = frame_source;
+        self
+    }
+
+    pub fn with_device(mut self, device: u32) -> Self {
+        self.config_webcam_device = device;
+        self
+    }
+
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.config_webcam_device_id = Some(device_id.into());
+        self
+    }
+
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.config_webcam_width = width;
+        self.config_webcam_height = height;
+        self
+    }
+
+    pub fn with_framerate(mut self, framerate: u32) -> Self {
+        self.config_webcam_framerate = framerate;
+        self
+    }
+
+    pub fn with_format(mut self, format: WebcamFormat) -> Self {
+        self.config_webcam_format = format;
+        self
+    }
+
+    pub fn with_flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.config_flip_horizontal = horizontal;
+        self.config_flip_vertical = vertical;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: FrameRotation) -> Self {
+        self.config_rotation = rotation;
+        self
+    }
+
+    pub fn with_filter(mut self, filter_type: SmoothingFilterType, length: u32) -> Self {
+        self.config_filter_type = filter_type;
+        self.config_filter_length = length;
+        self
+    }
+
+    // Detector settings of preset, can be switched at runtime by engine set_preset()
+    pub fn with_preset(mut self, preset: DetectorPreset) -> Self {
+        let params = preset.params();
+        self.config_min_face_size = params.min_face_size;
+        self.config_score_thresh = params.score_thresh;
+        self.config_pyramid_scale = params.pyramid_scale;
+        self.config_slide_window = params.slide_window;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.config_smoothing = smoothing;
+        self
+    }
+
+    pub fn with_deadzone(mut self, deadzone_x: f32, deadzone_y: f32) -> Self {
+        self.config_deadzone_x = deadzone_x;
+        self.config_deadzone_y = deadzone_y;
+        self
+    }
+
+    pub fn with_sensitivity(mut self, sensitivity_x: f32, sensitivity_y: f32) -> Self {
+        self.config_sensitivity_x = sensitivity_x;
+        self.config_sensitivity_y = sensitivity_y;
+        self
+    }
+
+    pub fn with_coordinate_mode(mut self, coordinate_mode: CoordinateMode) -> Self {
+        self.config_coordinate_mode = coordinate_mode;
+        self
+    }
+
+    pub fn with_roi(mut self, roi: Option<(u32, u32, u32, u32)>) -> Self {
+        self.config_roi = roi;
+        self
+    }
+
+    pub fn with_model_path(mut self, model_path: impl Into<String>) -> Self {
+        self.config_model_path = model_path.into();
+        self
+    }
+
+    pub fn with_model_bytes(mut self, model: &'static [u8]) -> Self {
+        self.config_model_bytes = Some(model);
+        self
+    }
+
+    pub fn with_face_detector(mut self, face_detector: FaceDetectorFactory) -> Self {
+        self.config_face_detector = Some(face_detector);
+        self
+    }
+}
+
+// Capture and detection loop of one camera with copy of engine settings,
+// run on WebcamFacialEngine thread
+struct CaptureTask {
+    camera_id: u32,
+    task_running: Arc<AtomicBool>,
+    task_paused: Arc<AtomicBool>,
+    task_reset_filter: Arc<AtomicBool>,
+    task_calibration: Arc<Mutex<(f32, f32, f32, f32)>>,
+    task_started: Arc<AtomicBool>,
+    task_active_config: Arc<Mutex<Option<ActiveCameraConfig>>>,
+    task_activity: ActivityGuard, // Task counts as running until run() returns
+    capture_activity: Arc<CaptureActivity>,
+    capture_started: Instant,
+    sender: Sender<WebcamFacialData>,
+    receiver: Receiver<WebcamFacialData>,
+    multi_sender: Sender<Vec<WebcamFacialData>>,
+    multi_receiver: Receiver<Vec<WebcamFacialData>>,
+    diagnostics_sender: Option<Sender<WebcamFacialDiagnostics>>,
+    stats_sender: Sender<WebcamFacialStats>,
+    error_sender: Sender<WebcamFacialError>,
+    #[cfg(feature = "blink")]
+    blink_sender: Sender<WebcamFacialEyes>,
+    #[cfg(feature = "frame_preview")]
+    preview_sender: Option<Sender<image::RgbaImage>>,
+    #[cfg(feature = "frame_preview")]
+    overlay: Option<([u8; 4], u32)>, // Color and thickness of face boxes drawn into preview
+    #[cfg(feature = "frame_preview")]
+    preview_mirror: bool,
+    rgb_needed: bool,
+    frame_source: WebcamFacialSource,
+    camera_settings: CameraSettings,
+    camera_timeout: Duration,
+    reconnect_interval: Duration,
+    warmup_frames: u32,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+    rotation: FrameRotation,
+    fov: f32,
+    pixel_aspect: f32,
+    reference_face_width: f32,
+    detection_interval: u32,
+    min_frame_interval: Duration,
+    filter_type: SmoothingFilterType,
+    filter_length: u32,
+    smoothing: f32,
+    size_smoothing: f32,
+    deadzone_x: f32,
+    deadzone_y: f32,
+    sensitivity_x: f32,
+    sensitivity_y: f32,
+    coordinate_mode: CoordinateMode,
+    tracking_distance: f32,
+    detection_scale: f32,
+    roi: Option<(u32, u32, u32, u32)>,
+    task_min_face_size: Arc<AtomicU32>,
+    task_score_thresh: Arc<AtomicU64>,
+    min_emit_score: f64,
+    acquire_score: f64,
+    keep_score: f64,
+    min_track_box_area: u32,
+    max_faces: usize,
+    face_selection: FaceSelection,
+    lost_grace_frames: u32,
+    hold_duration: Option<Duration>,
+    #[cfg(feature = "blink")]
+    blink_threshold: f32,
+    task_pyramid_scale: Arc<AtomicU32>,
+    task_slide_window: Arc<AtomicU64>,
+    model: DetectorModel,
+    face_detector: Option<FaceDetectorFactory>,
+    #[cfg(feature = "network")]
+    network_address: Option<String>,
+    #[cfg(feature = "network")]
+    network_format: NetworkFormat,
+}
+
+impl CaptureTask {
+    // Runs until task_running is cleared or capture fails, false only if camera or detector setup failed
+    fn run(self) -> bool {
+        let Self {
+            camera_id,
+            task_running,
+            task_paused,
+            task_reset_filter,
+            task_calibration,
+            task_started,
+            task_active_config,
+            task_activity: _task_activity,
+            capture_activity,
+            capture_started,
+            sender,
+            receiver,
+            multi_sender,
+            multi_receiver,
+            diagnostics_sender,
+            stats_sender,
+            error_sender,
+            #[cfg(feature = "blink")]
+            blink_sender,
+            #[cfg(feature = "frame_preview")]
+            preview_sender,
+            #[cfg(feature = "frame_preview")]
+            overlay,
+            #[cfg(feature = "frame_preview")]
+            preview_mirror,
+            rgb_needed,
+            frame_source,
+            camera_settings,
+            camera_timeout,
+            reconnect_interval,
+            warmup_frames,
+            flip_horizontal,
+            flip_vertical,
+            rotation,
+            fov,
+            pixel_aspect,
+            reference_face_width,
+            detection_interval,
+            min_frame_interval,
+            filter_type,
+            filter_length,
+            smoothing,
+            size_smoothing,
+            deadzone_x,
+            deadzone_y,
+            sensitivity_x,
+            sensitivity_y,
+            coordinate_mode,
+            tracking_distance,
+            detection_scale,
+            roi,
+            task_min_face_size,
+            task_score_thresh,
+            min_emit_score,
+            acquire_score,
+            keep_score,
+            min_track_box_area,
+            max_faces,
+            face_selection,
+            lost_grace_frames,
+            hold_duration,
+            #[cfg(feature = "blink")]
+            blink_threshold,
+            task_pyramid_scale,
+            task_slide_window,
+            model,
+            face_detector,
+            #[cfg(feature = "network")]
+            network_address,
+            #[cfg(feature = "network")]
+            network_format,
+        } = self;
+        // Initialize webcam, dropped frames are counted over reconnects
+        let dropped_frames = Arc::new(AtomicU32::new(0));
+        let open_camera = || {
+            CameraThread::open(
+                frame_source.clone(),
+                camera_settings.clone(),
+                rgb_needed,
+                dropped_frames.clone(),
+                capture_activity.clone(),
+            )
+        };
+        let mut camera_thread = match open_camera() {
+            Ok(camera_thread) => camera_thread,
+            Err(error) => {
+                error!("{}", error);
+                let _ = error_sender.send(error);
+                return false;
+            }
+        };
+        // Initialize face detector
+        let mut min_face_size = task_min_face_size.load(Ordering::SeqCst);
+        let mut score_thresh = f64::from_bits(task_score_thresh.load(Ordering::SeqCst));
+        let mut pyramid_scale = f32::from_bits(task_pyramid_scale.load(Ordering::SeqCst));
+        let mut slide_window = slide_window_from_bits(task_slide_window.load(Ordering::SeqCst));
+        let detector = create_detector(
+            face_detector,
+            &model,
+            min_face_size,
+            score_thresh,
+            pyramid_scale,
+            slide_window,
+        );
+        let mut detector = match detector {
+            Ok(detector) => detector,
+            Err(error) => {
+                error!("{}", error);
+                let _ = error_sender.send(error);
+                return false;
+            }
+        };
+
+        // Network sink is optional, capture continues without it
+        #[cfg(feature = "network")]
+        let mut network_sink = network_address.and_then(|address| {
+            network::NetworkSink::open(&address, network_format)
+                .inspect(|_| info!("Sending data to {}", address))
+                .map_err(|error| {
+                    warn!("{}", error);
+                    let _ = error_sender.send(error);
+                })
+                .ok()
+        });
+
+        let mut filtered_data = WebcamFacialDataFiltered::new(filter_length, filter_type);
+        let mut smoothed_data = WebcamFacialDataSmoothed::new(smoothing, size_smoothing);
+        let mut velocity_data = WebcamFacialDataVelocity::new();
+        let mut tracker = WebcamFacialTracker::new(tracking_distance);
+        let mut grace_data = WebcamFacialDataGrace::new(lost_grace_frames);
+        let mut hold_data = WebcamFacialDataHold::new(hold_duration);
+        #[cfg(feature = "blink")]
+        let mut blink_detector =
+            blink::WebcamFacialBlinkDetector::new(blink_threshold, flip_horizontal);
+        info!("Capturing frames...");
+        let mut frame_counter: u32 = 0;
+        let mut stats_window = WebcamFacialStatsWindow::new();
+        let mut last_detection = Instant::now();
+        let mut was_paused = false;
+        let mut face_tracked = false;
+        let mut next_frame_at = capture_started;
+        let mut first_frame = true;
+        let mut warmup_remaining = warmup_frames;
+        // Reused every frame instead of allocating new grayscale image
+        let mut grayscale_buffer = Vec::new();
+        #[cfg(feature = "frame_preview")]
+        let mut overlay_faces: Vec<DetectedFace> = Vec::new();
+        while task_running.load(Ordering::SeqCst) {
+            // Limit loop rate to save CPU, capture thread waits meanwhile
+            sleep_while_running(
+                next_frame_at.saturating_duration_since(Instant::now()),
+                &task_running,
+            );
+            next_frame_at = Instant::now() + min_frame_interval;
+            // Get frame from buffer
+            let (mut frame, captured) =
+                match camera_thread.next_frame(camera_timeout, &task_running) {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => break,
+                    Err(error) if reconnect_interval.is_zero() => {
+                        // Setup succeeded, failure is reported by error event only
+                        error!("{}", error);
+                        let _ = error_sender.send(error);
+                        return true;
+                    }
+                    Err(error) => {
+                        // Camera unplugged or stalled, report it and reopen until it works again
+                        let error = WebcamFacialError::CameraDisconnected(error.to_string());
+                        warn!("{}", error);
+                        let _ = error_sender.send(error);
+                        let _ = send_latest(&multi_sender, &multi_receiver, Vec::new());
+                        let _ = send_latest(
+                            &sender,
+                            &receiver,
+                            WebcamFacialData {
+                                timestamp: capture_started.elapsed(),
+                                camera_id,
+                                ..Default::default()
+                            },
+                        );
+                        // Release camera before opening it again
+                        drop(camera_thread);
+                        camera_thread = loop {
+                            sleep_while_running(reconnect_interval, &task_running);
+                            if !task_running.load(Ordering::SeqCst) {
+                                return true;
+                            }
+                            match open_camera() {
+                                Ok(camera_thread) => break camera_thread,
+                                Err(error) => debug!("Reconnecting camera: {}", error),
+                            }
+                        };
+                        info!("Camera reconnected.");
+                        // Reconnected camera may negotiate other mode, report it again
+                        first_frame = true;
+                        warmup_remaining = warmup_frames;
+                        filtered_data = WebcamFacialDataFiltered::new(filter_length, filter_type);
+                        smoothed_data.reset();
+                        velocity_data.reset();
+                        tracker.reset();
+                        grace_data.reset();
+                        hold_data.reset();
+                        #[cfg(feature = "blink")]
+                        blink_detector.reset();
+                        continue;
+                    }
+                };
+            // Discard first frames while camera auto exposure settles
+            if warmup_remaining > 0 {
+                warmup_remaining -= 1;
+                continue;
+            }
+            // Camera really delivers frames, report start once
+            if first_frame {
+                first_frame = false;
+                let (width, height) = frame.dimensions();
+                if let Ok(mut active_config) = task_active_config.lock() {
+                    *active_config = Some(camera_thread.active_config((width, height)));
+                }
+                task_started.store(true, Ordering::SeqCst);
+            }
+            // Rotate and flip frame itself so coordinates, preview and bounding box stay consistent
+            rotation.apply(&mut frame);
+            frame.flip(flip_horizontal, flip_vertical);
+            // Preview is optional, skip frame if previous wasn't shown yet
+            #[cfg(feature = "frame_preview")]
+            if let (Some(frame_sender), CapturedFrame::Rgb(rgb_frame)) = (&preview_sender, &*frame)
+            {
+                let mut rgba_frame = preview::frame_to_rgba(rgb_frame);
+                // Faces of last detection, frames between detections show them too
+                if let Some((color, thickness)) = overlay {
+                    preview::draw_overlay(&mut rgba_frame, &overlay_faces, color, thickness);
+                }
+                // Mirror only shown frame, detection and coordinates keep camera orientation
+                if preview_mirror {
+                    image::imageops::flip_horizontal_in_place(&mut rgba_frame);
+                }
+                let _ = frame_sender.try_send(rgba_frame);
+            }
+
+            // Keep reading frames while paused so camera buffer stays fresh
+            if task_paused.load(Ordering::SeqCst) {
+                if !was_paused {
+                    debug!("Detection paused.");
+                    was_paused = true;
+                }
+                #[cfg(feature = "frame_preview")]
+                overlay_faces.clear();
+                last_detection = Instant::now();
+                continue;
+            }
+            if was_paused {
+                debug!("Detection resumed.");
+            }
+            // Start filters from scratch, face may have moved a lot while paused or app asked for it
+            if mem::take(&mut was_paused) | task_reset_filter.swap(false, Ordering::SeqCst) {
+                filtered_data = WebcamFacialDataFiltered::new(filter_length, filter_type);
+                smoothed_data.reset();
+                velocity_data.reset();
+                tracker.reset();
+                grace_data.reset();
+                hold_data.reset();
+                face_tracked = false;
+                #[cfg(feature = "blink")]
+                blink_detector.reset();
+            }
+            // Detect only every Nth frame, others are discarded
+            frame_counter = frame_counter.wrapping_add(1);
+            // is_multiple_of needs Rust 1.87
+            #[allow(clippy::manual_is_multiple_of)]
+            if frame_counter % detection_interval != 0 {
+                continue;
+            }
+            // Frame size may differ from requested resolution
+            let (frame_width, frame_height) = frame.dimensions();
+            if frame_width == 0 || frame_height == 0 {
+                warn!("Skipping empty frame.");
+                continue;
+            }
+            let (frame_fov, frame_pixel_aspect) =
+                rotation.projection(fov, pixel_aspect, (frame_width, frame_height));
+            let detection_started = Instant::now();
+            // Thresholds may be changed through controller while capturing
+            let (new_min_face_size, new_score_thresh) = (
+                task_min_face_size.load(Ordering::SeqCst),
+                f64::from_bits(task_score_thresh.load(Ordering::SeqCst)),
+            );
+            if (new_min_face_size, new_score_thresh) != (min_face_size, score_thresh) {
+                min_face_size = new_min_face_size;
+                score_thresh = new_score_thresh;
+                debug!(
+                    "Detector thresholds changed: min face size {}, score {}",
+                    min_face_size, score_thresh
+                );
+                detector.set_thresholds(min_face_size, score_thresh);
+            }
+            // Search density changes together with thresholds when preset is switched
+            let (new_pyramid_scale, new_slide_window) = (
+                f32::from_bits(task_pyramid_scale.load(Ordering::SeqCst)),
+                slide_window_from_bits(task_slide_window.load(Ordering::SeqCst)),
+            );
+            if (new_pyramid_scale, new_slide_window) != (pyramid_scale, slide_window) {
+                pyramid_scale = new_pyramid_scale;
+                slide_window = new_slide_window;
+                debug!(
+                    "Detector search changed: pyramid scale {}, slide window {:?}",
+                    pyramid_scale, slide_window
+                );
+                detector.set_search(pyramid_scale, slide_window);
+            }
+            // Convert frame region of interest to grayscale
+            let (roi_x, roi_y, roi_width, roi_height) = clamp_roi(roi, frame_width, frame_height);
+            let grayscale_image = frame.luma_region(
+                (roi_x, roi_y, roi_width, roi_height),
+                mem::take(&mut grayscale_buffer),
+            );
+            // Capture thread can fill frame buffer again while detecting
+            drop(frame);
+            let faces = detect_faces(
+                detector.as_mut(),
+                &grayscale_image,
+                (roi_x, roi_y),
+                detection_scale,
+            );
+
+            // Stats are informational, skip if runner didn't take previous yet
+            let _ = stats_sender.try_send(stats_window.push(
+                detection_started - last_detection,
+                detection_started.elapsed(),
+                dropped_frames.load(Ordering::SeqCst),
+            ));
+            last_detection = detection_started;
+
+            // Sort faces by human face probability, best candidates first,
+            // weak detections are treated as no face. Hysteresis: new face needs acquire score,
+            // while face is tracked lower keep score is enough, so face doesn't flicker at threshold.
+            // Small faces of distant people are ignored entirely
+            let emit_score = min_emit_score.max(if face_tracked {
+                keep_score
+            } else {
+                acquire_score
+            });
+            // Informational like stats, skipped if runner didn't take previous ones yet
+            if let Some(diagnostics_sender) = &diagnostics_sender {
+                let mut candidates = faces.clone();
+                candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+                let _ = diagnostics_sender.try_send(WebcamFacialDiagnostics {
+                    candidates,
+                    score_thresh,
+                    emit_score,
+                    min_track_box_area,
+                    timestamp: captured.saturating_duration_since(capture_started),
+                });
+            }
+            let mut sorted_faces: Vec<&DetectedFace> = faces
+                .iter()
+                .filter(|face| {
+                    face.score >= emit_score && face.width * face.height >= min_track_box_area
+                })
+                .collect();
+            face_tracked = !sorted_faces.is_empty();
+            let face_count = sorted_faces.len() as u32;
+            // In crowds only best faces are sorted and processed further
+            if max_faces > 0 && sorted_faces.len() > max_faces {
+                sorted_faces
+                    .select_nth_unstable_by(max_faces - 1, |a, b| b.score.total_cmp(&a.score));
+                sorted_faces.truncate(max_faces);
+            }
+            sorted_faces.sort_by(|a, b| b.score.total_cmp(&a.score));
+            #[cfg(feature = "frame_preview")]
+            if overlay.is_some() {
+                overlay_faces.clear();
+                overlay_faces.extend(sorted_faces.iter().map(|face| **face));
+            }
+
+            let mut all_facial_data: Vec<WebcamFacialData> = sorted_faces
+                .iter()
+                .map(|face| {
+                    facial_data_from_face(
+                        face,
+                        frame_width,
+                        frame_height,
+                        frame_fov,
+                        frame_pixel_aspect,
+                        reference_face_width,
+                    )
+                })
+                .collect();
+            tracker.assign(&mut all_facial_data);
+            let timestamp = captured.saturating_duration_since(capture_started);
+            for data in &mut all_facial_data {
+                data.timestamp = timestamp;
+                data.camera_id = camera_id;
+                data.face_count = face_count;
+            }
+
+            // Get face chosen by selection strategy (best candidate)
+            // Initialize zero values if face not found
+            let selected = face_selection.select(&all_facial_data);
+            // Eyes of selected face, measured on full resolution region of interest
+            #[cfg(feature = "blink")]
+            if let Some(eyes) = blink_detector.update(
+                &grayscale_image,
+                selected
+                    .map(|index| DetectedFace {
+                        x: sorted_faces[index].x - roi_x as i32,
+                        y: sorted_faces[index].y - roi_y as i32,
+                        ..*sorted_faces[index]
+                    })
+                    .as_ref(),
+            ) {
+                let _ = blink_sender.send(eyes);
+            }
+            grayscale_buffer = grayscale_image.into_raw();
+            let facial_data = selected.map(|index| all_facial_data[index]).map_or_else(
+                || {
+                    trace!("No faces found. Using default zero values.");
+                    WebcamFacialData {
+                        timestamp,
+                        camera_id,
+                        ..Default::default()
+                    }
+                },
+                |selected_face| {
+                    trace!("Selected face: {:?}", selected_face);
+                    selected_face
+                },
+            );
+            let all_facial_data = all_facial_data
+                .into_iter()
+                .map(|data| coordinate_mode.apply(data))
+                .collect();
+            if let Err(SendError(data)) =
+                send_latest(&multi_sender, &multi_receiver, all_facial_data)
+            {
+                error!("Failed to send task data: {:?}", data);
+            }
+
+            // Scaled before filters, so smoothing and deadzone work on output values
+            // Short detection gaps keep last face instead of reporting it lost
+            let facial_data = grace_data.apply(facial_data);
+            filtered_data.push(apply_sensitivity(facial_data, sensitivity_x, sensitivity_y));
+
+            // Velocity from smoothed movement, deadzone applied last for stable neutral position
+            let processed_data =
+                velocity_data.apply(smoothed_data.apply(filtered_data.get()), captured);
+            // Calibration before deadzone, so neutral position snaps to zero
+            let calibration = task_calibration
+                .lock()
+                .map_or((0.0, 0.0, 0.0, 0.0), |calibration| *calibration);
+            let processed_data = subtract_calibration(
+                processed_data,
+                coordinate_mode.calibration_offset(calibration),
+            );
+            let processed_data =
+                coordinate_mode.apply(apply_deadzone(processed_data, deadzone_x, deadzone_y));
+            // Lost face keeps last output position instead of snapping to zero values
+            let processed_data = hold_data.apply(processed_data);
+
+            #[cfg(feature = "network")]
+            if let Some(network_sink) = &mut network_sink {
+                network_sink.send(&processed_data);
+            }
+            // Send processed and filtered data
+            match send_latest(&sender, &receiver, processed_data) {
+                Ok(()) => trace!("Data from task sent."),
+
+                Err(SendError(data)) => error!("Failed to send task data: {:?}", data),
+            }
+        }
+        // Stop capture thread and release camera
+        drop(camera_thread);
+        true
+    }
+}
+
+// Capture and detection core without Bevy, ex. in CLI tool or other engine. Runs the pipeline
+// on own thread, Bevy plugin controller wraps one engine per camera
+pub struct WebcamFacialEngine {
+    config: WebcamFacialConfig,
+    pub(crate) sender: Sender<WebcamFacialData>,
+    pub(crate) receiver: Receiver<WebcamFacialData>,
+    pub(crate) multi_sender: Sender<Vec<WebcamFacialData>>,
+    pub(crate) multi_receiver: Receiver<Vec<WebcamFacialData>>,
+    diagnostics_sender: Sender<WebcamFacialDiagnostics>,
+    pub(crate) diagnostics_receiver: Receiver<WebcamFacialDiagnostics>,
+    stats_sender: Sender<WebcamFacialStats>,
+    pub(crate) stats_receiver: Receiver<WebcamFacialStats>,
+    error_sender: Sender<WebcamFacialError>,
+    pub(crate) error_receiver: Receiver<WebcamFacialError>,
+    #[cfg(feature = "blink")]
+    blink_sender: Sender<WebcamFacialEyes>,
+    #[cfg(feature = "blink")]
+    pub(crate) blink_receiver: Receiver<WebcamFacialEyes>,
+    #[cfg(feature = "frame_preview")]
+    frame_sender: Sender<image::RgbaImage>,
+    #[cfg(feature = "frame_preview")]
+    pub(crate) frame_receiver: Receiver<image::RgbaImage>,
+    running: Arc<AtomicBool>,      // Cleared to stop capture thread
+    min_face_size: Arc<AtomicU32>, // Read by task before every detection
+    score_thresh: Arc<AtomicU64>,  // f64 bits
+    pyramid_scale: Arc<AtomicU32>, // f32 bits, read by task before every detection like thresholds
+    slide_window: Arc<AtomicU64>,  // Step x << 32 | step y
+    calibration: Arc<Mutex<(f32, f32, f32, f32)>>, // Neutral center_x, center_y, normalized_x, normalized_y, read by task before deadzone
+    paused: Arc<AtomicBool>,
+    reset_filter: Arc<AtomicBool>, // Set by reset_filter(), taken by task before next detection
+    started: Arc<AtomicBool>,      // Set by task when first frame arrives, taken by plugin runner
+    active_config: Arc<Mutex<Option<ActiveCameraConfig>>>, // Set by task before started, cleared when it finishes
+    activity: Arc<CaptureActivity>, // Tasks and capture threads still running
+    capture_started: Instant,       // Data timestamps are relative to it
+    last_face_data: Option<WebcamFacialData>, // Last polled data with face
+    thread: Option<JoinHandle<bool>>,
+    stats: WebcamFacialStats,
+}
+
+impl WebcamFacialEngine {
+    pub fn new(config: &WebcamFacialConfig) -> Self {
+        // Add thread channels for data exchange
+        // Data channels keep only latest detections, oldest are dropped when full
+        let channel_capacity = config.config_channel_capacity.max(1);
+        let (sender, receiver) = bounded(channel_capacity);
+        let (multi_sender, multi_receiver) = bounded(channel_capacity);
+        let (diagnostics_sender, diagnostics_receiver) = bounded(channel_capacity);
+        let (stats_sender, stats_receiver) = bounded(1);
+        let (error_sender, error_receiver) = unbounded();
+        // Every eye transition matters (blink is a click), none are dropped
+        #[cfg(feature = "blink")]
+        let (blink_sender, blink_receiver) = unbounded();
+        #[cfg(feature = "frame_preview")]
+        let (frame_sender, frame_receiver) = bounded(1);
+        Self {
+            config: config.clone(),
+            sender,
+            receiver,
+            multi_sender,
+            multi_receiver,
+            diagnostics_sender,
+            diagnostics_receiver,
+            stats_sender,
+            stats_receiver,
+            error_sender,
+            error_receiver,
+            #[cfg(feature = "blink")]
+            blink_sender,
+            #[cfg(feature = "blink")]
+            blink_receiver,
+            #[cfg(feature = "frame_preview")]
+            frame_sender,
+            #[cfg(feature = "frame_preview")]
+            frame_receiver,
+            running: Arc::new(AtomicBool::new(false)),
+            min_face_size: Arc::new(AtomicU32::new(config.config_min_face_size)),
+            score_thresh: Arc::new(AtomicU64::new(config.config_score_thresh.to_bits())),
+            pyramid_scale: Arc::new(AtomicU32::new(config.config_pyramid_scale.to_bits())),
+            slide_window: Arc::new(AtomicU64::new(slide_window_bits(
+                config.config_slide_window,
+            ))),
+            calibration: Arc::new(Mutex::new((0.0, 0.0, 0.0, 0.0))),
+            paused: Arc::new(AtomicBool::new(false)),
+            reset_filter: Arc::new(AtomicBool::new(false)),
+            started: Arc::new(AtomicBool::new(false)),
+            active_config: Arc::new(Mutex::new(None)),
+            activity: Arc::new(CaptureActivity::default()),
+            capture_started: Instant::now(),
+            last_face_data: None,
+            thread: None,
+            stats: WebcamFacialStats::default(),
+        }
+    }
+
+    // Engine with frame source and detector checked up front (validate())
+    pub fn try_new(config: &WebcamFacialConfig) -> Result<Self, WebcamFacialError> {
+        let engine = Self::new(config);
+        engine.validate()?;
+        Ok(engine)
+    }
+
+    // Frame source exists and detector loads, so setup errors can be handled before capture starts.
+    // Camera isn't opened, busy camera or unsupported mode are still reported by capture
+    pub fn validate(&self) -> Result<(), WebcamFacialError> {
+        self.validate_source()?;
+        create_detector(
+            self.config.config_face_detector.clone(),
+            &self.detector_model(),
+            self.min_face_size(),
+            self.score_thresh(),
+            self.pyramid_scale(),
+            self.slide_window(),
+        )?;
+        Ok(())
+    }
+
+    // Start capture thread, nothing happens while it's already running
+    pub fn start(&mut self) -> Result<(), WebcamFacialError> {
+        if self.is_running() {
+            return Ok(());
+        }
+        self.join();
+        let capture_task = self.capture_task();
+        self.running.store(true, Ordering::SeqCst);
+        let thread = thread::Builder::new()
+            .name("webcam_facial_engine".to_string())
+            .spawn(move || capture_task.run())
+            .map_err(|err| {
+                self.running.store(false, Ordering::SeqCst);
+                WebcamFacialError::CameraOpen(err.to_string())
+            })?;
+        info!("Capture started.");
+        self.thread = Some(thread);
+        Ok(())
+    }
+
+    // Stop capture and wait until camera is released
+    pub fn stop(&mut self) {
+        self.request_stop();
+        self.join();
+    }
+
+    // False also when capture stopped by itself, errors are returned by poll_error()
+    pub fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    // Latest data since last poll (stale data dropped, calibration applied), None if nothing new
+    pub fn poll(&mut self) -> Option<WebcamFacialData> {
+        if let Ok(stats) = self.stats_receiver.try_recv() {
+            self.stats = stats;
+        }
+        // Eye changes are sent as Bevy events only
+        #[cfg(feature = "blink")]
+        while self.blink_receiver.try_recv().is_ok() {}
+        let data = self
+            .receiver
+            .try_iter()
+            .filter(|data| !self.is_stale(data))
+            .last()?;
+        self.last_face_data = data.face_found.then_some(data);
+        Some(data)
+    }
+
+    // All faces of latest detection, None if nothing new
+    pub fn poll_faces(&mut self) -> Option<Vec<WebcamFacialData>> {
+        self.multi_receiver.try_iter().last()
+    }
+
+    // Detector candidates of latest frame with config_diagnostics, None if nothing new
+    pub fn poll_diagnostics(&mut self) -> Option<WebcamFacialDiagnostics> {
+        self.diagnostics_receiver.try_iter().last()
+    }
+
+    pub fn poll_error(&mut self) -> Option<WebcamFacialError> {
+        self.error_receiver.try_recv().ok()
+    }
+
+    // Capture performance updated by poll()
+    pub const fn stats(&self) -> &WebcamFacialStats {
+        &self.stats
+    }
+
+    // Settings engine was created with, runtime changes (device, thresholds) are read by their getters
+    pub const fn config(&self) -> &WebcamFacialConfig {
+        &self.config
+    }
+
+    // Identifier of engine (config_camera_id), same as camera_id of its data
+    pub const fn camera_id(&self) -> u32 {
+        self.config.config_camera_id
+    }
+
+    // Switch to other webcam device, used from next start()
+    pub fn set_device(&mut self, device: u32) {
+        self.config.config_webcam_device = device;
+        self.config.config_webcam_device_id = None;
+    }
+
+    pub const fn device(&self) -> u32 {
+        self.config.config_webcam_device
+    }
+
+    // Resolution camera delivers while capturing (selected from config_webcam_resolutions), None when stopped
+    pub fn resolution(&self) -> Option<(u32, u32)> {
+        self.active_config().map(|config| config.resolution)
+    }
+
+    // Format, resolution and framerate camera delivers after negotiation, None until first frame arrives
+    pub fn active_config(&self) -> Option<ActiveCameraConfig> {
+        self.active_config
+            .lock()
+            .ok()
+            .and_then(|active_config| *active_config)
+    }
+
+    // Adjustable settings of configured webcam device (brightness, exposure...), Linux only
+    pub fn controls(&self) -> Result<Vec<WebcamControl>, WebcamFacialError> {
+        self.camera_source()?;
+        webcam_controls(self.resolved_device())
+    }
+
+    // Set webcam control by name from controls(), applies immediately also while capturing
+    pub fn set_control(&self, name: &str, value: i64) -> Result<(), WebcamFacialError> {
+        self.camera_source()?;
+        set_webcam_control(self.resolved_device(), name, value)
+    }
+
+    // Files and custom sources have no camera controls
+    fn camera_source(&self) -> Result<(), WebcamFacialError> {
+        match self.config.config_frame_source {
+            WebcamFacialSource::Camera => Ok(()),
+            _ => Err(WebcamFacialError::UnsupportedControl(
+                "frame source is not a webcam".to_string(),
+            )),
+        }
+    }
+
+    // Switch all detector settings at once, takes effect on next detection
+    pub fn set_preset(&self, preset: DetectorPreset) {
+        let params = preset.params();
+        self.set_min_face_size(params.min_face_size);
+        self.set_score_thresh(params.score_thresh);
+        self.pyramid_scale
+            .store(params.pyramid_scale.to_bits(), Ordering::SeqCst);
+        self.slide_window
+            .store(slide_window_bits(params.slide_window), Ordering::SeqCst);
+    }
+
+    // Preset matching current detector settings, None after thresholds were changed separately
+    pub fn preset(&self) -> Option<DetectorPreset> {
+        let params = DetectorParams {
+            min_face_size: self.min_face_size(),
+            score_thresh: self.score_thresh(),
+            pyramid_scale: self.pyramid_scale(),
+            slide_window: self.slide_window(),
+        };
+        DetectorPreset::ALL
+            .into_iter()
+            .find(|preset| preset.params() == params)
+    }
+
+    pub fn pyramid_scale(&self) -> f32 {
+        f32::from_bits(self.pyramid_scale.load(Ordering::SeqCst))
+    }
+
+    pub fn slide_window(&self) -> (u32, u32) {
+        slide_window_from_bits(self.slide_window.load(Ordering::SeqCst))
+    }
+
+    // Minimal face size in pixels used by detector, takes effect on next detection
+    pub fn set_min_face_size(&self, min_face_size: u32) {
+        self.min_face_size.store(min_face_size, Ordering::SeqCst);
+    }
+
+    pub fn min_face_size(&self) -> u32 {
+        self.min_face_size.load(Ordering::SeqCst)
+    }
+
+    // Face score threshold used by detector (lower - more detections), takes effect on next detection
+    pub fn set_score_thresh(&self, score_thresh: f64) {
+        self.score_thresh
+            .store(score_thresh.to_bits(), Ordering::SeqCst);
+    }
+
+    pub fn score_thresh(&self) -> f64 {
+        f64::from_bits(self.score_thresh.load(Ordering::SeqCst))
+    }
+
+    // Smoothing filter used by capture
+    pub const fn filter_type(&self) -> SmoothingFilterType {
+        self.config.config_filter_type
+    }
+
+    // Stop capture and wait until it finished and camera is released, false on timeout
+    // (warning tells how long camera capture is blocked)
+    pub fn stop_and_wait(&mut self, timeout: Duration) -> bool {
+        self.request_stop();
+        let started = Instant::now();
+        while !self.activity.is_idle() {
+            let waited = started.elapsed();
+            if waited >= timeout {
+                match self.activity.capture_blocked() {
+                    Some(blocked) => warn!(
+                        "Capture didn't stop in {:?}, camera capture is blocked for {:?}.",
+                        timeout, blocked
+                    ),
+                    None => warn!("Capture didn't stop in {:?}.", timeout),
+                }
+                return false;
+            }
+            thread::sleep((timeout - waited).min(STOP_POLL_INTERVAL));
+        }
+        true
+    }
+
+    // Stop detection and data, but keep camera open and detector loaded for instant resume
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    // Drop filter history so next data starts from current face instead of gliding from old position
+    pub fn reset_filter(&self) {
+        self.reset_filter.store(true, Ordering::SeqCst);
+    }
+
+    // Single detection without continuous capture, blocks until frame after warmup is captured and searched.
+    // Returns selected face unfiltered in configured coordinate mode, None if no face found.
+    // Fails while capture is running, camera is already in use
+    pub fn detect_once(&self) -> Result<Option<WebcamFacialData>, WebcamFacialError> {
+        if self.running.load(Ordering::SeqCst) {
+            return Err(WebcamFacialError::CameraOpen(
+                "capture task is running".to_string(),
+            ));
+        }
+        let running = AtomicBool::new(true);
+        let camera_thread = CameraThread::open(
+            self.config.config_frame_source.clone(),
+            self.camera_settings(),
+            false,
+            Arc::new(AtomicU32::new(0)),
+            self.activity.clone(),
+        )?;
+        let mut detector = create_detector(
+            self.config.config_face_detector.clone(),
+            &self.detector_model(),
+            self.min_face_size(),
+            self.score_thresh(),
+            self.pyramid_scale(),
+            self.slide_window(),
+        )?;
+        // Running flag is never cleared, so frame is always returned
+        let next_frame = || {
+            camera_thread
+                .next_frame(self.config.config_webcam_timeout, &running)?
+                .ok_or_else(|| WebcamFacialError::CaptureFailed("capture stopped".to_string()))
+        };
+        // Frames captured while auto exposure settles are discarded
+        for _ in 0..self.warmup_frames() {
+            next_frame()?;
+        }
+        let (mut frame, captured) = next_frame()?;
+        self.config.config_rotation.apply(&mut frame);
+        frame.flip(
+            self.config.config_flip_horizontal,
+            self.config.config_flip_vertical,
+        );
+        let (frame_width, frame_height) = frame.dimensions();
+        if frame_width == 0 || frame_height == 0 {
+            return Err(WebcamFacialError::CaptureFailed("empty frame".to_string()));
+        }
+        let (roi_x, roi_y, roi_width, roi_height) =
+            clamp_roi(self.config.config_roi, frame_width, frame_height);
+        let grayscale_image = frame.luma_region((roi_x, roi_y, roi_width, roi_height), Vec::new());
+        drop(frame);
+        let faces = detect_faces(
+            detector.as_mut(),
+            &grayscale_image,
+            (roi_x, roi_y),
+            self.config.config_detection_scale.clamp(0.01, 1.0),
+        );
+        // No face is tracked yet, so new face needs acquire score
+        let min_emit_score = f64::from(
+            self.config
+                .config_min_emit_score
+                .max(self.config.config_acquire_score),
+        );
+        let mut faces: Vec<&DetectedFace> = faces
+            .iter()
+            .filter(|face| {
+                face.score >= min_emit_score
+                    && face.width * face.height >= self.config.config_min_track_box_area
+            })
+            .collect();
+        faces.sort_by(|a, b| b.score.total_cmp(&a.score));
+        let (fov, pixel_aspect) = self.projection();
+        let (frame_fov, frame_pixel_aspect) =
+            self.config
+                .config_rotation
+                .projection(fov, pixel_aspect, (frame_width, frame_height));
+        let timestamp = captured.saturating_duration_since(self.capture_started);
+        let all_facial_data: Vec<WebcamFacialData> = faces
+            .iter()
+            .map(|face| WebcamFacialData {
+                timestamp,
+                camera_id: self.config.config_camera_id,
+                face_count: faces.len() as u32,
+                ..facial_data_from_face(
+                    face,
+                    frame_width,
+                    frame_height,
+                    frame_fov,
+                    frame_pixel_aspect,
+                    self.config.config_reference_face_width_cm,
+                )
+            })
+            .collect();
+        Ok(self
+            .config
+            .config_face_selection
+            .select(&all_facial_data)
+            .map(|index| {
+                let data = apply_sensitivity(
+                    all_facial_data[index],
+                    self.config.config_sensitivity_x,
+                    self.config.config_sensitivity_y,
+                );
+                self.apply_calibration(self.config.config_coordinate_mode.apply(data))
+            }))
+    }
+
+    // Face was in last polled data
+    pub const fn face_present(&self) -> bool {
+        self.last_face_data.is_some()
+    }
+
+    // Current face center becomes neutral (0, 0) of center_x, center_y, normalized_x and normalized_y
+    // in following data, until reset. Returns false without face in last polled data
+    pub fn calibrate(&mut self) -> bool {
+        let Some(data) = self.last_face_data else {
+            return false;
+        };
+        self.add_calibration(&data);
+        true
+    }
+
+    pub fn reset_calibration(&mut self) {
+        if let Ok(mut calibration) = self.calibration.lock() {
+            *calibration = (0.0, 0.0, 0.0, 0.0);
+        }
+    }
+
+    // Face center of already calibrated data becomes neutral position, offsets add up
+    pub(crate) fn add_calibration(&self, data: &WebcamFacialData) {
+        if let Ok(mut calibration) = self.calibration.lock() {
+            calibration.0 += data.center_x;
+            calibration.1 += data.center_y;
+            calibration.2 += data.normalized_x;
+            calibration.3 += data.normalized_y;
+        }
+        info!("Calibrated neutral face position.");
+    }
+
+    // Data of detect_once() and mock, task calibrates its data itself
+    pub(crate) fn apply_calibration(&self, data: WebcamFacialData) -> WebcamFacialData {
+        let calibration = self
+            .calibration
+            .lock()
+            .map_or((0.0, 0.0, 0.0, 0.0), |calibration| *calibration);
+        subtract_calibration(data, calibration)
+    }
+
+    // Data captured longer than config_max_staleness ago (ZERO - never stale)
+    pub(crate) fn is_stale(&self, data: &WebcamFacialData) -> bool {
+        !self.config.config_max_staleness.is_zero()
+            && self
+                .capture_started
+                .elapsed()
+                .saturating_sub(data.timestamp)
+                > self.config.config_max_staleness
+    }
+
+    // Tell capture thread to stop without waiting for it
+    pub(crate) fn request_stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    // Wait for finished or stopping thread, panic is reported by poll_error()
+    fn join(&mut self) {
+        if let Some(Err(error)) = self.join_thread() {
+            error!("{}", error);
+            let _ = self.error_sender.send(error);
+        }
+    }
+
+    fn join_thread(&mut self) -> Option<Result<bool, WebcamFacialError>> {
+        let status = self
+            .thread
+            .take()?
+            .join()
+            .map_err(|payload| WebcamFacialError::from_panic(payload.as_ref()));
+        self.clear_active_config();
+        self.stats = WebcamFacialStats::default();
+        Some(status)
+    }
+
+    // Camera is closed, set when task finished
+    fn clear_active_config(&self) {
+        if let Ok(mut active_config) = self.active_config.lock() {
+            *active_config = None;
+        }
+    }
+
+    // Device behind stable id if configured, falls back to device number when id isn't found
+    fn resolved_device(&self) -> u32 {
+        let Some(device_id) = &self.config.config_webcam_device_id else {
+            return self.config.config_webcam_device;
+        };
+        resolve_webcam_device_id(device_id).unwrap_or_else(|| {
+            warn!(
+                "Webcam '{}' not found, using device {}.",
+                device_id, self.config.config_webcam_device
+            );
+            self.config.config_webcam_device
+        })
+    }
+
+    // Webcam device present (Linux only) or source files exist, custom sources aren't checked
+    fn validate_source(&self) -> Result<(), WebcamFacialError> {
+        match &self.config.config_frame_source {
+            #[cfg(target_os = "linux")]
+            WebcamFacialSource::Camera => {
+                let device = self.resolved_device();
+                if list_webcam_devices()
+                    .iter()
+                    .any(|info| info.device == device)
+                {
+                    Ok(())
+                } else {
+                    Err(WebcamFacialError::CameraOpen(format!(
+                        "webcam device {} not found",
+                        device
+                    )))
+                }
+            }
+            WebcamFacialSource::ImageDirectory(path) | WebcamFacialSource::Gif(path)
+                if !path.exists() =>
+            {
+                Err(WebcamFacialError::CameraOpen(format!(
+                    "'{}' not found",
+                    path.display()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn detector_model(&self) -> DetectorModel {
+        DetectorModel {
+            path: self.config.config_model_path.clone(),
+            bytes: self.config.config_model_bytes,
+            checksum: self.config.config_model_checksum,
+        }
+    }
+
+    fn camera_settings(&self) -> CameraSettings {
+        CameraSettings {
+            device: self.resolved_device(),
+            resolutions: if self.config.config_webcam_resolutions.is_empty() {
+                vec![(
+                    self.config.config_webcam_width,
+                    self.config.config_webcam_height,
+                )]
+            } else {
+                self.config.config_webcam_resolutions.clone()
+            },
+            framerate: self.config.config_webcam_framerate,
+            format: self.config.config_webcam_format,
+            nearest_mode: self.config.config_webcam_nearest_mode,
+        }
+    }
+
+    // Files and custom sources have no exposure to settle
+    fn warmup_frames(&self) -> u32 {
+        if matches!(self.config.config_frame_source, WebcamFacialSource::Camera) {
+            self.config.config_warmup_frames
+        } else {
+            0
+        }
+    }
+
+    // Field of view and pixel aspect limited to usable values
+    fn projection(&self) -> (f32, f32) {
+        let pixel_aspect = if self.config.config_pixel_aspect > 0.0 {
+            self.config.config_pixel_aspect
+        } else {
+            1.0
+        };
+        (
+            self.config.config_webcam_fov.clamp(1.0, 179.0),
+            pixel_aspect,
+        )
+    }
+
+    // Task with current settings, its start is origin of data timestamps
+    fn capture_task(&mut self) -> CaptureTask {
+        let capture_started = Instant::now();
+        self.capture_started = capture_started;
+        let config = &self.config;
+        #[cfg(feature = "frame_preview")]
+        let preview_sender = config
+            .config_expose_frame
+            .then(|| self.frame_sender.clone());
+        let (fov, pixel_aspect) = self.projection();
+        CaptureTask {
+            camera_id: config.config_camera_id,
+            task_running: self.running.clone(),
+            task_paused: self.paused.clone(),
+            task_reset_filter: self.reset_filter.clone(),
+            task_calibration: self.calibration.clone(),
+            task_started: self.started.clone(),
+            task_active_config: self.active_config.clone(),
+            task_activity: ActivityGuard::task(self.activity.clone()),
+            capture_activity: self.activity.clone(),
+            capture_started,
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            multi_sender: self.multi_sender.clone(),
+            multi_receiver: self.multi_receiver.clone(),
+            diagnostics_sender: config
+                .config_diagnostics
+                .then(|| self.diagnostics_sender.clone()),
+            stats_sender: self.stats_sender.clone(),
+            error_sender: self.error_sender.clone(),
+            #[cfg(feature = "blink")]
+            blink_sender: self.blink_sender.clone(),
+            // RGB frame is needed only for preview, detection works on grayscale
+            #[cfg(feature = "frame_preview")]
+            rgb_needed: preview_sender.is_some(),
+            #[cfg(not(feature = "frame_preview"))]
+            rgb_needed: false,
+            #[cfg(feature = "frame_preview")]
+            preview_sender,
+            #[cfg(feature = "frame_preview")]
+            overlay: config
+                .config_overlay_color
+                .map(|color| (color, config.config_overlay_thickness.max(1))),
+            #[cfg(feature = "frame_preview")]
+            preview_mirror: config.config_preview_mirror,
+            frame_source: config.config_frame_source.clone(),
+            camera_settings: self.camera_settings(),
+            camera_timeout: config.config_webcam_timeout,
+            reconnect_interval: config.config_webcam_reconnect_interval,
+            warmup_frames: self.warmup_frames(),
+            flip_horizontal: config.config_flip_horizontal,
+            flip_vertical: config.config_flip_vertical,
+            rotation: config.config_rotation,
+            fov,
+            pixel_aspect,
+            reference_face_width: config.config_reference_face_width_cm,
+            detection_interval: config.config_detection_interval.max(1),
+            min_frame_interval: config.config_min_frame_interval,
+            filter_type: config.config_filter_type,
+            filter_length: config.config_filter_length,
+            smoothing: config.config_smoothing,
+            size_smoothing: config
+                .config_size_smoothing
+                .unwrap_or(config.config_smoothing),
+            deadzone_x: config.config_deadzone_x,
+            deadzone_y: config.config_deadzone_y,
+            sensitivity_x: config.config_sensitivity_x,
+            sensitivity_y: config.config_sensitivity_y,
+            coordinate_mode: config.config_coordinate_mode,
+            tracking_distance: config.config_tracking_distance,
+            detection_scale: config.config_detection_scale.clamp(0.01, 1.0),
+            roi: config.config_roi,
+            task_min_face_size: self.min_face_size.clone(),
+            task_score_thresh: self.score_thresh.clone(),
+            min_emit_score: f64::from(config.config_min_emit_score),
+            acquire_score: f64::from(config.config_acquire_score),
+            keep_score: f64::from(config.config_keep_score),
+            min_track_box_area: config.config_min_track_box_area,
+            max_faces: config.config_max_faces,
+            face_selection: config.config_face_selection,
+            lost_grace_frames: config.config_lost_grace_frames,
+            hold_duration: config
+                .config_hold_last_on_dropout
+                .then_some(config.config_hold_max_duration),
+            #[cfg(feature = "blink")]
+            blink_threshold: config.config_blink_threshold,
+            task_pyramid_scale: self.pyramid_scale.clone(),
+            task_slide_window: self.slide_window.clone(),
+            model: self.detector_model(),
+            face_detector: config.config_face_detector.clone(),
+            #[cfg(feature = "network")]
+            network_address: config.config_network_address.clone(),
+            #[cfg(feature = "network")]
+            network_format: config.config_network_format,
+        }
+    }
+}
+
+// Capture lifecycle driven by plugin runner without blocking Bevy systems
+#[cfg(feature = "bevy")]
+impl WebcamFacialEngine {
+    // Other sources than webcam count as available
+    pub(crate) fn camera_available(&self) -> bool {
+        !matches!(self.config.config_frame_source, WebcamFacialSource::Camera)
+            || self.validate_source().is_ok()
+    }
+
+    // Thread was started and isn't joined yet (running or finished)
+    pub(crate) const fn has_thread(&self) -> bool {
+        self.thread.is_some()
+    }
+
+    // First frame arrived since last call
+    pub(crate) fn take_started(&self) -> bool {
+        self.started.swap(false, Ordering::SeqCst)
+    }
+
+    // Join thread once it finished, None while running or without thread. Panic is returned as error
+    pub(crate) fn try_join(&mut self) -> Option<Result<bool, WebcamFacialError>> {
+        if !self.thread.as_ref()?.is_finished() {
+            return None;
+        }
+        self.request_stop();
+        self.join_thread()
+    }
+
+    // Stop without waiting, thread finishes and releases camera on its own
+    pub(crate) fn detach(&mut self) {
+        self.request_stop();
+        self.thread = None;
+    }
+}
+
+impl Drop for WebcamFacialEngine {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FaceDetector;
+    use image::GrayImage;
+    use std::{path::PathBuf, sync::Arc};
+
+    // Face left edge follows frame brightness, so data tells which frame was searched
+    struct BrightnessDetector;
+
+    impl FaceDetector for BrightnessDetector {
+        fn detect(&mut self, image: &GrayImage) -> Vec<DetectedFace> {
+            vec![DetectedFace {
+                x: i32::from(image.get_pixel(0, 0).0[0] / 8),
+                y: 4,
+                width: 6,
+                height: 6,
+                score: 5.0,
+            }]
+        }
+    }
+
+    #[test]
+    fn image_directory_in_order_and_looped() {
+        // frame_0.png, frame_1.png, frame_2.png - 32x24 grey 40, 120, 200
+        let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets/test_sequence");
+        let config = WebcamFacialConfig {
+            config_webcam_framerate: 200,
+            config_channel_capacity: 16,
+            ..WebcamFacialConfig::default()
+        }
+        .with_frame_source(WebcamFacialSource::ImageDirectory(directory))
+        .with_filter(SmoothingFilterType::NoFilter, 1)
+        .with_face_detector(Arc::new(|| Ok(Box::new(BrightnessDetector))));
+        let mut engine = WebcamFacialEngine::try_new(&config).unwrap();
+        engine.start().unwrap();
+        let positions: Vec<f32> = (0..7)
+            .map(|_| {
+                let data = engine
+                    .receiver
+                    .recv_timeout(Duration::from_secs(5))
+                    .unwrap();
+                assert!(data.face_found);
+                data.x
+            })
+            .collect();
+        engine.stop();
+        assert!(engine.poll_error().is_none());
+        assert!(positions[0] < positions[1] && positions[1] < positions[2]);
+        for (index, position) in positions.iter().enumerate().skip(3) {
+            assert_eq!(*position, positions[index - 3]);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::{any::Any, fmt};
+
+use crate::WebcamMode;
+
+// Errors reported by the capture task through WebcamFacialErrorEvent
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebcamFacialError {
+    CameraOpen(String),                       // Camera device missing or busy
+    CameraStart(String),  // Camera refused resolution, framerate or stream start
+    DetectorLoad(String), // Face recognition model could not be loaded
+    ModelChecksum(u64, u64), // Expected and found model checksum, wrong or corrupt model file
+    CaptureFailed(String), // Camera stopped delivering frames
+    MalformedFrame(String), // Frame data doesn't match its size or can't be decoded, frame is skipped
+    CameraDisconnected(String), // Capture failed mid-session, camera is being reopened
+    UnsupportedMode(String, Vec<WebcamMode>), // Requested mode and supported modes
+    UnsupportedControl(String), // Camera control missing, read-only or rejected by camera
+    NetworkSink(String),    // Network sink address invalid or socket unavailable
+    TaskPanicked(String),   // Capture task terminated abnormally
+}
+
+impl fmt::Display for WebcamFacialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CameraOpen(reason) => write!(f, "Failed to open camera: {reason}"),
+            Self::CameraStart(reason) => write!(f, "Failed to start camera: {reason}"),
+            Self::DetectorLoad(reason) => write!(f, "Failed to load detector: {reason}"),
+            Self::ModelChecksum(expected, found) => write!(
+                f,
+                "Model checksum {found:016x} doesn't match expected {expected:016x}, wrong or corrupt model file"
+            ),
+            Self::CaptureFailed(reason) => write!(f, "Failed to capture frame: {reason}"),
+            Self::MalformedFrame(reason) => write!(f, "Malformed frame: {reason}"),
+            Self::CameraDisconnected(reason) => {
+                write!(f, "Camera disconnected, reconnecting: {reason}")
+            }
+            Self::UnsupportedMode(requested, available) => {
+                let available: Vec<String> = available
+                    .iter()
+                    .map(|mode| format!("{} {}x{}", mode.format, mode.width, mode.height))
+                    .collect();
+                write!(
+                    f,
+                    "Camera doesn't support {requested}, available modes: {}",
+                    available.join(", ")
+                )
+            }
+            Self::UnsupportedControl(reason) => {
+                write!(f, "Camera control not supported: {reason}")
+            }
+            Self::NetworkSink(reason) => write!(f, "Failed to open network sink: {reason}"),
+            Self::TaskPanicked(reason) => write!(f, "Capture task panicked: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for WebcamFacialError {}
+
+impl WebcamFacialError {
+    // Panic payload is usually &str or String message
+    pub(crate) fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|reason| (*reason).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown reason".to_string());
+        Self::TaskPanicked(reason)
+    }
+}
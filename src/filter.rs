@@ -1,26 +1,92 @@
+use std::{
+    f32::consts::TAU,
+    time::{Duration, Instant},
+};
+
 use crate::WebcamFacialData;
 
-pub struct WebcamFacialDataFiltered(Vec<WebcamFacialData>, u32, SmoothingFilterType);
+// Interval at which exponential smoothing factors and filter length apply as given (default 15 fps),
+// other capture rates and detection intervals get factors converted and filter window sized by time
+// so smoothing lasts the same time
+const SMOOTHING_REFERENCE_INTERVAL: f32 = 1.0 / 15.0;
+
+pub struct WebcamFacialDataFiltered(
+    Vec<WebcamFacialData>,
+    u32,
+    SmoothingFilterType,
+    Option<OneEuroState>,
+);
 
 #[allow(unused)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SmoothingFilterType {
     MeanMedian,
     LowPass(f32), // LowPass filter with alpha parameter
+    // Speed adaptive low pass of face position using frame timestamps, cutoffs in Hz:
+    // min_cutoff - jitter at rest (lower - less jitter), beta - lag when moving (higher - less lag)
+    OneEuro {
+        min_cutoff: f32,
+        beta: f32,
+        d_cutoff: f32,
+    },
     NoFilter,
 }
 
+// One euro filter of position fields (center_x, center_y, x, y, normalized_x, normalized_y, yaw, pitch)
+// of one tracked face, with timestamp of last detection
+struct OneEuroState(u32, Duration, [OneEuroValue; 8]);
+// Percent fields are filtered in normalized units, so beta means the same for every field
+const ONE_EURO_SCALE: [f32; 8] = [50.0, 50.0, 50.0, 50.0, 1.0, 1.0, 1.0, 1.0];
+
+// Filtered value and its filtered speed per second
+#[derive(Clone, Copy)]
+struct OneEuroValue {
+    value: f32,
+    speed: f32,
+}
+
+impl OneEuroValue {
+    fn apply(&mut self, value: f32, delta: f32, min_cutoff: f32, beta: f32, d_cutoff: f32) {
+        let speed = (value - self.value) / delta;
+        self.speed += one_euro_alpha(d_cutoff, delta) * (speed - self.speed);
+        let cutoff = beta.mul_add(self.speed.abs(), min_cutoff);
+        self.value += one_euro_alpha(cutoff, delta) * (value - self.value);
+    }
+}
+
+// Smoothing factor of low pass with cutoff frequency for sample interval
+fn one_euro_alpha(cutoff: f32, delta: f32) -> f32 {
+    let tau = 1.0 / (TAU * cutoff.max(f32::EPSILON));
+    1.0 / (1.0 + tau / delta)
+}
+
 impl WebcamFacialDataFiltered {
     pub const fn new(length: u32, filter_type: SmoothingFilterType) -> Self {
-        Self(Vec::new(), length, filter_type)
+        Self(Vec::new(), length, filter_type, None)
     }
 
     #[allow(unused)]
     pub fn push(&mut self, data: WebcamFacialData) {
-        if self.0.len() >= self.1 as usize {
+        // Data without timestamps keeps last length elements
+        if data.timestamp.is_zero() && self.0.len() >= self.1 as usize {
             self.0.remove(0); // Remove the oldest element
         }
+        // Window spans length - 1 reference intervals, half frame interval tolerance for timestamp jitter
+        let frame_interval = self.0.last().map_or(0.0, |last| {
+            data.timestamp.saturating_sub(last.timestamp).as_secs_f32()
+        });
+        let window = ((self.1.max(1) - 1) as f32)
+            .mul_add(SMOOTHING_REFERENCE_INTERVAL, frame_interval / 2.0);
         self.0.push(data);
+        while self.0.len() > 1
+            && data
+                .timestamp
+                .saturating_sub(self.0[0].timestamp)
+                .as_secs_f32()
+                >= window
+        {
+            self.0.remove(0);
+        }
     }
 
     #[allow(unused)]
@@ -31,22 +97,27 @@ impl WebcamFacialDataFiltered {
         match self.2 {
             SmoothingFilterType::MeanMedian => self.mean_median_filter(),
             SmoothingFilterType::LowPass(alpha) => self.low_pass_filter(alpha),
-            SmoothingFilterType::NoFilter => WebcamFacialData {
-                center_x: self.0[self.0.len() - 1].center_x,
-                center_y: self.0[self.0.len() - 1].center_y,
-                x: self.0[self.0.len() - 1].x,
-                y: self.0[self.0.len() - 1].y,
-                width: self.0[self.0.len() - 1].width,
-                height: self.0[self.0.len() - 1].height,
-                score: self.0[self.0.len() - 1].score,
-            },
+            SmoothingFilterType::OneEuro {
+                min_cutoff,
+                beta,
+                d_cutoff,
+            } => self.one_euro_filter(min_cutoff, beta, d_cutoff),
+            SmoothingFilterType::NoFilter => self.0[self.0.len() - 1],
         }
     }
 
     #[allow(unused)]
     fn low_pass_filter(&self, alpha: f32) -> WebcamFacialData {
         let mut filtered_data = WebcamFacialData::default();
+        let mut previous_timestamp = None;
         for data in &self.0 {
+            let alpha = previous_timestamp.map_or(alpha, |previous_timestamp| {
+                1.0 - time_scaled(
+                    1.0 - alpha,
+                    data.timestamp.saturating_sub(previous_timestamp),
+                )
+            });
+            previous_timestamp = Some(data.timestamp);
             filtered_data.center_x += alpha * (data.center_x - filtered_data.center_x);
             filtered_data.center_y += alpha * (data.center_y - filtered_data.center_y);
             filtered_data.x += alpha * (data.x - filtered_data.x);
@@ -54,11 +125,82 @@ impl WebcamFacialDataFiltered {
             filtered_data.width += alpha * (data.width - filtered_data.width);
             filtered_data.height += alpha * (data.height - filtered_data.height);
             filtered_data.score += alpha * (data.score - filtered_data.score);
+            filtered_data.face_found = data.face_found;
+            filtered_data.id = data.id;
+            filtered_data.timestamp = data.timestamp;
+            filtered_data.looking_at_screen = data.looking_at_screen;
+            filtered_data.camera_id = data.camera_id;
+            filtered_data.face_count = data.face_count;
+            filtered_data.area = data.area;
+            filtered_data.aspect_ratio = data.aspect_ratio;
+            filtered_data.quality = data.quality;
+            filtered_data.normalized_x += alpha * (data.normalized_x - filtered_data.normalized_x);
+            filtered_data.normalized_y += alpha * (data.normalized_y - filtered_data.normalized_y);
+            filtered_data.normalized_width +=
+                alpha * (data.normalized_width - filtered_data.normalized_width);
+            filtered_data.normalized_height +=
+                alpha * (data.normalized_height - filtered_data.normalized_height);
+            filtered_data.yaw += alpha * (data.yaw - filtered_data.yaw);
+            filtered_data.pitch += alpha * (data.pitch - filtered_data.pitch);
+            filtered_data.distance_cm += alpha * (data.distance_cm - filtered_data.distance_cm);
         }
 
         filtered_data
     }
 
+    // Filters latest data only, state continues between frames of the same face
+    fn one_euro_filter(&mut self, min_cutoff: f32, beta: f32, d_cutoff: f32) -> WebcamFacialData {
+        let data = self.0[self.0.len() - 1];
+        if !data.face_found {
+            self.3 = None;
+            return data;
+        }
+        let position = [
+            data.center_x,
+            data.center_y,
+            data.x,
+            data.y,
+            data.normalized_x,
+            data.normalized_y,
+            data.yaw,
+            data.pitch,
+        ];
+        let position: [f32; 8] = std::array::from_fn(|i| position[i] / ONE_EURO_SCALE[i]);
+        // Different tracked face starts from its own position
+        let Some(OneEuroState(_, previous_timestamp, mut values)) =
+            self.3.take().filter(|state| state.0 == data.id)
+        else {
+            self.3 = Some(OneEuroState(
+                data.id,
+                data.timestamp,
+                position.map(|value| OneEuroValue { value, speed: 0.0 }),
+            ));
+            return data;
+        };
+        let delta = data
+            .timestamp
+            .saturating_sub(previous_timestamp)
+            .as_secs_f32();
+        if delta > 0.0 {
+            for (value, position) in values.iter_mut().zip(position) {
+                value.apply(position, delta, min_cutoff, beta, d_cutoff);
+            }
+        }
+        self.3 = Some(OneEuroState(data.id, data.timestamp, values));
+        let position: [f32; 8] = std::array::from_fn(|i| values[i].value * ONE_EURO_SCALE[i]);
+        WebcamFacialData {
+            center_x: position[0],
+            center_y: position[1],
+            x: position[2],
+            y: position[3],
+            normalized_x: position[4],
+            normalized_y: position[5],
+            yaw: position[6],
+            pitch: position[7],
+            ..data
+        }
+    }
+
     #[allow(unused)]
     fn mean_median_filter(&self) -> WebcamFacialData {
         let num_elements = self.0.len();
@@ -70,6 +212,13 @@ impl WebcamFacialDataFiltered {
         let mut width_sum = 0.0;
         let mut height_sum = 0.0;
         let mut score_sum = 0.0;
+        let mut normalized_x_sum = 0.0;
+        let mut normalized_y_sum = 0.0;
+        let mut normalized_width_sum = 0.0;
+        let mut normalized_height_sum = 0.0;
+        let mut yaw_sum = 0.0;
+        let mut pitch_sum = 0.0;
+        let mut distance_cm_sum = 0.0;
 
         for data in &self.0 {
             center_x_sum += data.center_x;
@@ -79,6 +228,13 @@ impl WebcamFacialDataFiltered {
             width_sum += data.width;
             height_sum += data.height;
             score_sum += data.score;
+            normalized_x_sum += data.normalized_x;
+            normalized_y_sum += data.normalized_y;
+            normalized_width_sum += data.normalized_width;
+            normalized_height_sum += data.normalized_height;
+            yaw_sum += data.yaw;
+            pitch_sum += data.pitch;
+            distance_cm_sum += data.distance_cm;
         }
 
         WebcamFacialData {
@@ -89,6 +245,287 @@ impl WebcamFacialDataFiltered {
             width: width_sum / num_elements as f32,
             height: height_sum / num_elements as f32,
             score: score_sum / num_elements as f32,
+            face_found: self.0[num_elements - 1].face_found,
+            normalized_x: normalized_x_sum / num_elements as f32,
+            normalized_y: normalized_y_sum / num_elements as f32,
+            normalized_width: normalized_width_sum / num_elements as f32,
+            normalized_height: normalized_height_sum / num_elements as f32,
+            yaw: yaw_sum / num_elements as f32,
+            pitch: pitch_sum / num_elements as f32,
+            distance_cm: distance_cm_sum / num_elements as f32,
+            id: self.0[num_elements - 1].id,
+            timestamp: self.0[num_elements - 1].timestamp,
+            looking_at_screen: self.0[num_elements - 1].looking_at_screen,
+            camera_id: self.0[num_elements - 1].camera_id,
+            face_count: self.0[num_elements - 1].face_count,
+            area: self.0[num_elements - 1].area,
+            aspect_ratio: self.0[num_elements - 1].aspect_ratio,
+            quality: self.0[num_elements - 1].quality,
+            ..Default::default()
+        }
+    }
+}
+
+// Exponential moving average applied on top of the smoothing filter,
+// 0.0 - no smoothing, approaching 1.0 - heavy smoothing
+// Position and size (width, height, distance) have own factors, box size jitters more than center
+pub struct WebcamFacialDataSmoothed(Option<WebcamFacialData>, f32, f32);
+
+impl WebcamFacialDataSmoothed {
+    pub fn new(smoothing: f32, size_smoothing: f32) -> Self {
+        Self(
+            None,
+            smoothing.clamp(0.0, 0.99),
+            size_smoothing.clamp(0.0, 0.99),
+        )
+    }
+
+    // Forget previous position so new face doesn't lerp from stale one
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn apply(&mut self, data: WebcamFacialData) -> WebcamFacialData {
+        if !data.face_found {
+            self.reset();
+            return data;
+        }
+        // Different tracked face, start from its own position
+        let smoothed = match self.0.take().filter(|previous| previous.id == data.id) {
+            Some(previous) => {
+                let delta = data.timestamp.saturating_sub(previous.timestamp);
+                let smoothing = time_scaled(self.1, delta);
+                let size_smoothing = time_scaled(self.2, delta);
+                WebcamFacialData {
+                    center_x: ema(previous.center_x, data.center_x, smoothing),
+                    center_y: ema(previous.center_y, data.center_y, smoothing),
+                    x: ema(previous.x, data.x, smoothing),
+                    y: ema(previous.y, data.y, smoothing),
+                    width: ema(previous.width, data.width, size_smoothing),
+                    height: ema(previous.height, data.height, size_smoothing),
+                    normalized_x: ema(previous.normalized_x, data.normalized_x, smoothing),
+                    normalized_y: ema(previous.normalized_y, data.normalized_y, smoothing),
+                    normalized_width: ema(
+                        previous.normalized_width,
+                        data.normalized_width,
+                        size_smoothing,
+                    ),
+                    normalized_height: ema(
+                        previous.normalized_height,
+                        data.normalized_height,
+                        size_smoothing,
+                    ),
+                    yaw: ema(previous.yaw, data.yaw, smoothing),
+                    pitch: ema(previous.pitch, data.pitch, smoothing),
+                    distance_cm: ema(previous.distance_cm, data.distance_cm, size_smoothing),
+                    ..data
+                }
+            }
+            None => data,
+        };
+        self.0 = Some(smoothed);
+        smoothed
+    }
+}
+
+// Last found face held for a few frames without detection (config_lost_grace_frames),
+// so face isn't lost when detector misses it briefly
+pub struct WebcamFacialDataGrace(Option<WebcamFacialData>, u32, u32);
+
+impl WebcamFacialDataGrace {
+    pub const fn new(grace_frames: u32) -> Self {
+        Self(None, 0, grace_frames)
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = None;
+        self.1 = 0;
+    }
+
+    pub fn apply(&mut self, data: WebcamFacialData) -> WebcamFacialData {
+        if data.face_found {
+            self.0 = Some(data);
+            self.1 = 0;
+            return data;
+        }
+        match self.0.filter(|_| self.1 < self.2) {
+            Some(last_data) => {
+                self.1 += 1;
+                // Held position, but current frame time and face count
+                WebcamFacialData {
+                    timestamp: data.timestamp,
+                    face_count: data.face_count,
+                    ..last_data
+                }
+            }
+            None => {
+                self.reset();
+                data
+            }
+        }
+    }
+}
+
+// Last position kept after face is lost for up to max duration (config_hold_last_on_dropout),
+// so camera bound to head doesn't jump to center on every dropout. None - disabled
+pub struct WebcamFacialDataHold(Option<WebcamFacialData>, Option<Duration>);
+
+impl WebcamFacialDataHold {
+    pub const fn new(max_duration: Option<Duration>) -> Self {
+        Self(None, max_duration)
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn apply(&mut self, data: WebcamFacialData) -> WebcamFacialData {
+        let Some(max_duration) = self.1 else {
+            return data;
+        };
+        if data.face_found {
+            self.0 = Some(data);
+            return data;
+        }
+        match self
+            .0
+            .filter(|last_data| data.timestamp.saturating_sub(last_data.timestamp) <= max_duration)
+        {
+            // Face is reported lost, but position stays where it was last seen
+            Some(last_data) => WebcamFacialData {
+                face_found: false,
+                looking_at_screen: false,
+                velocity_x: 0.0,
+                velocity_y: 0.0,
+                timestamp: data.timestamp,
+                face_count: data.face_count,
+                ..last_data
+            },
+            None => {
+                self.reset();
+                data
+            }
+        }
+    }
+}
+
+// Face movement speed from consecutive detections in normalized units per second,
+// zero when face was just found so reacquiring doesn't produce a spike
+pub struct WebcamFacialDataVelocity(Option<(WebcamFacialData, Instant)>);
+
+impl WebcamFacialDataVelocity {
+    pub const fn new() -> Self {
+        Self(None)
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn apply(&mut self, mut data: WebcamFacialData, timestamp: Instant) -> WebcamFacialData {
+        if !data.face_found {
+            self.reset();
+            return data;
+        }
+        if let Some((previous, previous_timestamp)) =
+            self.0.take().filter(|(previous, _)| previous.id == data.id)
+        {
+            let delta = timestamp
+                .saturating_duration_since(previous_timestamp)
+                .as_secs_f32();
+            if delta > 0.0 {
+                data.velocity_x = (data.normalized_x - previous.normalized_x) / delta;
+                data.velocity_y = (data.normalized_y - previous.normalized_y) / delta;
+            }
+        }
+        self.0 = Some((data, timestamp));
+        data
+    }
+}
+
+// Amplify face center movement from frame center per axis (1.0 - unchanged)
+pub fn apply_sensitivity(
+    mut data: WebcamFacialData,
+    sensitivity_x: f32,
+    sensitivity_y: f32,
+) -> WebcamFacialData {
+    data.center_x *= sensitivity_x;
+    data.normalized_x *= sensitivity_x;
+    data.center_y *= sensitivity_y;
+    data.normalized_y *= sensitivity_y;
+    data
+}
+
+// Snap face center to zero when it's near frame center (deadzone in normalized units 0.0 .. 1.0)
+pub fn apply_deadzone(
+    mut data: WebcamFacialData,
+    deadzone_x: f32,
+    deadzone_y: f32,
+) -> WebcamFacialData {
+    if data.normalized_x.abs() < deadzone_x {
+        data.center_x = 0.0;
+        data.normalized_x = 0.0;
+        data.yaw = 0.0;
+    }
+    if data.normalized_y.abs() < deadzone_y {
+        data.center_y = 0.0;
+        data.normalized_y = 0.0;
+        data.pitch = 0.0;
+    }
+    data
+}
+
+fn ema(previous: f32, current: f32, smoothing: f32) -> f32 {
+    smoothing.mul_add(previous - current, current)
+}
+
+// Weight kept from previous value after delta, given weight applies to reference interval
+fn time_scaled(smoothing: f32, delta: Duration) -> f32 {
+    if delta.is_zero() {
+        return smoothing;
+    }
+    smoothing.powf(delta.as_secs_f32() / SMOOTHING_REFERENCE_INTERVAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Filtered normalized_x after constant input for elapsed seconds at fps
+    fn low_pass_after(fps: u32, elapsed: u32, length: u32) -> f32 {
+        let mut filter = WebcamFacialDataFiltered::new(length, SmoothingFilterType::LowPass(0.1));
+        let mut filtered = WebcamFacialData::default();
+        for frame in 1..=fps * elapsed {
+            filter.push(WebcamFacialData {
+                normalized_x: 1.0,
+                face_found: true,
+                timestamp: Duration::from_secs(1) * frame / fps,
+                ..Default::default()
+            });
+            filtered = filter.get();
+        }
+        filtered.normalized_x
+    }
+
+    #[test]
+    fn time_scaled_same_over_same_time() {
+        let once = time_scaled(0.8, Duration::from_secs(1) / 15);
+        let twice = time_scaled(0.8, Duration::from_secs(1) / 30).powi(2);
+        assert!((once - 0.8).abs() < 1e-6);
+        assert!((once - twice).abs() < 1e-6);
+    }
+
+    #[test]
+    fn low_pass_same_at_capture_rates() {
+        for length in [1, 5, 10] {
+            let reference = low_pass_after(15, 2, length);
+            for fps in [30, 60] {
+                let filtered = low_pass_after(fps, 2, length);
+                assert!(
+                    (filtered - reference).abs() < 1e-3,
+                    "length {length}: {filtered} at {fps} fps, {reference} at 15 fps"
+                );
+            }
         }
     }
 }
@@ -0,0 +1,103 @@
+use bevy::{
+    ecs::{
+        event::EventReader,
+        system::{Res, ResMut, Resource},
+    },
+    time::{Real, Time},
+};
+use std::time::Duration;
+
+use crate::{WebcamFacialData, WebcamFacialDataEvent};
+
+// Face data of one camera interpolated to render frames (config_interpolation), capture at 30 fps
+// looks steppy at 144 fps otherwise. Follows detections one capture interval behind,
+// between last two data. Updated every frame in WebcamFacialSet, read data after it
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct WebcamFacialInterpolated {
+    pub data: Option<WebcamFacialData>, // None until first data arrives
+    pub camera_id: u32,                 // Camera which data is interpolated
+    previous: Option<WebcamFacialData>,
+    latest: Option<WebcamFacialData>,
+    latest_at: Duration, // Real app time when latest data arrived
+}
+
+impl WebcamFacialInterpolated {
+    pub const fn new(camera_id: u32) -> Self {
+        Self {
+            data: None,
+            camera_id,
+            previous: None,
+            latest: None,
+            latest_at: Duration::ZERO,
+        }
+    }
+
+    // Data at real app time, between previous and latest data by time passed since latest arrived
+    pub fn sample(&self, now: Duration) -> Option<WebcamFacialData> {
+        let latest = self.latest?;
+        let Some(previous) = self.previous.filter(|previous| {
+            previous.face_found && latest.face_found && previous.id == latest.id
+        }) else {
+            // Face appears, disappears or changes at once
+            return Some(latest);
+        };
+        let interval = latest.timestamp.saturating_sub(previous.timestamp);
+        if interval.is_zero() {
+            return Some(latest);
+        }
+        let t = (now.saturating_sub(self.latest_at).as_secs_f32() / interval.as_secs_f32())
+            .clamp(0.0, 1.0);
+        Some(interpolate(&previous, &latest, t))
+    }
+
+    fn push(&mut self, data: WebcamFacialData, now: Duration) {
+        self.previous = self.latest.replace(data);
+        self.latest_at = now;
+    }
+}
+
+// Position, size and angles blended, other fields from latest data
+fn interpolate(previous: &WebcamFacialData, latest: &WebcamFacialData, t: f32) -> WebcamFacialData {
+    let lerp = |a: f32, b: f32| (b - a).mul_add(t, a);
+    WebcamFacialData {
+        center_x: lerp(previous.center_x, latest.center_x),
+        center_y: lerp(previous.center_y, latest.center_y),
+        x: lerp(previous.x, latest.x),
+        y: lerp(previous.y, latest.y),
+        width: lerp(previous.width, latest.width),
+        height: lerp(previous.height, latest.height),
+        normalized_x: lerp(previous.normalized_x, latest.normalized_x),
+        normalized_y: lerp(previous.normalized_y, latest.normalized_y),
+        normalized_width: lerp(previous.normalized_width, latest.normalized_width),
+        normalized_height: lerp(previous.normalized_height, latest.normalized_height),
+        yaw: lerp(previous.yaw, latest.yaw),
+        pitch: lerp(previous.pitch, latest.pitch),
+        distance_cm: lerp(previous.distance_cm, latest.distance_cm),
+        window_x: lerp(previous.window_x, latest.window_x),
+        window_y: lerp(previous.window_y, latest.window_y),
+        timestamp: previous.timestamp
+            + latest
+                .timestamp
+                .saturating_sub(previous.timestamp)
+                .mul_f32(t),
+        ..*latest
+    }
+}
+
+// Store new data events and interpolate for current frame
+pub fn webcam_facial_interpolation_updater(
+    mut interpolated: ResMut<WebcamFacialInterpolated>,
+    mut data_events: EventReader<WebcamFacialDataEvent>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed();
+    let camera_id = interpolated.camera_id;
+    for data in data_events
+        .read()
+        .map(|event| event.0)
+        .filter(|data| data.camera_id == camera_id)
+    {
+        interpolated.push(data, now);
+    }
+    interpolated.data = interpolated.sample(now);
+}
@@ -1,67 +1,128 @@
 // Plugin that reads webcamera, detects face calculates frame box
 // and sends coordinates to Bevy as Event.
+// Capture and detection core (WebcamFacialEngine) builds without Bevy ('bevy' feature).
 
-use bevy::{
-    app::{App, Plugin, Update},
-    ecs::{
-        component::Component,
-        entity::Entity,
-        event::{Event, EventWriter},
-        system::{Commands, Query, ResMut, Resource},
-    },
-    log::{debug, error, info},
-    tasks::{AsyncComputeTaskPool, Task},
+use crossbeam_channel::{Receiver, SendError, Sender, TrySendError};
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
 };
 
-use crossbeam_channel::{bounded, Receiver, SendError, Sender};
-use futures_lite::future;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
-
-// rustface detector
-use rustface::ImageData;
 // image utils
-use image::{ImageBuffer, Luma};
+use image::{imageops, GrayImage, RgbImage};
+// Horizontal field of view of a typical webcam for head angle estimation (config_webcam_fov default)
+pub(crate) const WEBCAM_HORIZONTAL_FOV: f32 = 60.0;
+// Width of adult face box for distance estimation (config_reference_face_width_cm default)
+pub(crate) const REFERENCE_FACE_WIDTH_CM: f32 = 15.0;
+// Looking at screen heuristic: max face angle from camera axis and face box aspect ratio range
+const LOOKING_MAX_ANGLE: f32 = 0.35;
+const LOOKING_ASPECT_RATIO: (f32, f32) = (0.75, 1.33);
+// Quality hints: distance from frame edge in pixels treated as touching it, score mapped to 1.0
+const QUALITY_EDGE_MARGIN: i32 = 2;
+const QUALITY_FULL_SCORE: f32 = 20.0;
+// How often sleeping or waiting task checks if it was stopped
+pub(crate) const STOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
 // Data filter/smoothing
 mod filter;
 pub use filter::SmoothingFilterType;
-use filter::WebcamFacialDataFiltered;
-
-pub struct WebcamFacialPlugin {
-    pub config_webcam_device: u32,
-    pub config_webcam_width: u32,
-    pub config_webcam_height: u32,
-    pub config_webcam_framerate: u32,
-    pub config_webcam_autostart: bool,
-    pub config_filter_type: SmoothingFilterType,
-    pub config_filter_length: u32,
-}
-// Plugin configuration for webcam to be accesible from plugin system
-#[derive(Resource)]
-pub struct WebcamFacialController {
-    pub sender: Sender<WebcamFacialData>,
-    pub receiver: Receiver<WebcamFacialData>,
-    pub control: bool,
-    pub status: Arc<AtomicBool>,
-    config_device: u32,
-    config_width: u32,
-    config_height: u32,
-    config_framerate: u32,
-    config_filter_type: SmoothingFilterType,
-    config_filter_length: u32,
-}
-
-#[derive(Component)]
-struct WebcamFacialTask(Task<bool>);
-
-// WebcamFacialEvent event for sending WebcamFacialData to main Bevy app
-#[derive(Event)]
-pub struct WebcamFacialDataEvent(pub WebcamFacialData);
+use filter::{
+    apply_deadzone, apply_sensitivity, WebcamFacialDataFiltered, WebcamFacialDataGrace,
+    WebcamFacialDataHold, WebcamFacialDataSmoothed, WebcamFacialDataVelocity,
+};
+// Upright frame of sideways camera
+mod rotation;
+pub use rotation::FrameRotation;
+// Origin and axes of coordinates
+mod coordinates;
+pub use coordinates::CoordinateMode;
+// Tracked face choice
+mod selection;
+pub use selection::FaceSelection;
+// Face ids across frames
+mod tracker;
+use tracker::WebcamFacialTracker;
+// Errors reported by the task
+mod error;
+pub use error::WebcamFacialError;
+// Capture/detection performance
+mod stats;
+pub use stats::WebcamFacialStats;
+use stats::WebcamFacialStatsWindow;
+// Face rectangle in preview coordinates
+#[cfg(feature = "bevy")]
+mod bounding_box;
+// Head tracking transform
+#[cfg(feature = "bevy")]
+mod transform;
+#[cfg(feature = "bevy")]
+pub use transform::{facial_data_to_transform, FacialTransformAxes};
+// Eye closure of detected face
+#[cfg(feature = "blink")]
+mod blink;
+#[cfg(feature = "blink")]
+pub use blink::WebcamFacialEyes;
+// Data interpolated to render frames
+#[cfg(feature = "bevy")]
+mod interpolation;
+#[cfg(feature = "bevy")]
+pub use interpolation::WebcamFacialInterpolated;
+// Face controlled cursor
+#[cfg(feature = "cursor")]
+mod cursor;
+#[cfg(feature = "cursor")]
+pub use cursor::WebcamFacialCursor;
+// Data over UDP to other programs
+#[cfg(feature = "network")]
+mod network;
+#[cfg(feature = "network")]
+pub use network::NetworkFormat;
+// Captured frame as Bevy Image
+#[cfg(feature = "frame_preview")]
+mod preview;
+#[cfg(feature = "frame_preview")]
+pub use preview::WebcamFacialFrame;
+// Face detection
+mod detector;
+use detector::DetectorModel;
+pub use detector::{
+    model_checksum, validate_model, DetectedFace, DetectorParams, DetectorPreset, FaceDetector,
+    FaceDetectorFactory, RustfaceDetector, SEETA_MODEL_CHECKSUM,
+};
+// Scripted data without camera
+#[cfg(feature = "bevy")]
+mod mock;
+#[cfg(feature = "bevy")]
+pub use mock::WebcamFacialMock;
+// Webcam capture
+mod camera;
+pub use camera::{
+    list_webcam_devices, resolve_webcam_device_id, ActiveCameraConfig, CapturedFrame, FrameSource,
+    WebcamControl, WebcamDeviceInfo, WebcamFormat, WebcamMode,
+};
+// Capture and detection core without Bevy and its settings
+mod engine;
+pub use engine::{WebcamFacialConfig, WebcamFacialEngine};
+// Bevy plugin, controller component wrapping engine, events and systems
+#[cfg(feature = "bevy")]
+mod plugin;
+#[cfg(all(feature = "bevy", feature = "blink"))]
+pub use plugin::WebcamFacialBlinkEvent;
+#[cfg(feature = "bevy")]
+pub use plugin::{
+    webcam_facial_active, WebcamFacialController, WebcamFacialDataEvent,
+    WebcamFacialDiagnosticsEvent, WebcamFacialErrorEvent, WebcamFacialFaceAcquiredEvent,
+    WebcamFacialFaceLostEvent, WebcamFacialMultiFaceEvent, WebcamFacialNoCameraAvailableEvent,
+    WebcamFacialPlugin, WebcamFacialResolutionEvent, WebcamFacialSet, WebcamFacialStartedEvent,
+    WebcamFacialStoppedEvent,
+};
+// Frames from files or own source instead of webcam
+mod source;
+pub use source::{FrameSourceFactory, ImageSequenceSource, WebcamFacialSource};
 
 // Data structure to be exchanged with Bevy
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WebcamFacialData {
     pub center_x: f32,
     pub center_y: f32,
@@ -70,239 +131,384 @@ pub struct WebcamFacialData {
     pub width: f32,
     pub height: f32,
     pub score: f32,
+    pub face_found: bool,
+    pub normalized_x: f32,
+    pub normalized_y: f32,
+    pub normalized_width: f32,
+    pub normalized_height: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+    pub id: u32,
+    pub timestamp: Duration,
+    pub looking_at_screen: bool,
+    pub camera_id: u32,
+    pub face_count: u32,
+    pub area: i32,
+    pub aspect_ratio: f32,
+    pub distance_cm: f32,
+    pub window_x: f32,
+    pub window_y: f32,
+    pub quality: WebcamFacialQuality,
 }
 
-impl Plugin for WebcamFacialPlugin {
-    fn build(&self, app: &mut App) {
-        // Add thread channels for data exchange
-        let (task_channel_sender, task_channel_receiver) = bounded(1);
-        let task_status = Arc::new(AtomicBool::new(false));
-        // Store plugin control,data channels and settings in a resource
-        let plugin = WebcamFacialController {
-            sender: task_channel_sender,
-            receiver: task_channel_receiver,
-            control: self.config_webcam_autostart,
-            status: task_status,
+// Hints whether detection is trustworthy, all default without face
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WebcamFacialQuality {
+    pub touches_edge: bool, // Box touches frame edge, partial face with unreliable center
+    pub normalized_score: f32, // Score in range 0.0 .. 1.0, QUALITY_FULL_SCORE and above is 1.0
+    pub relative_size: f32, // Box area relative to frame area, 0.0 .. 1.0
+}
 
-            config_device: self.config_webcam_device,
-            config_width: self.config_webcam_width,
-            config_height: self.config_webcam_height,
-            config_framerate: self.config_webcam_framerate,
-            config_filter_type: self.config_filter_type,
-            config_filter_length: self.config_filter_length,
-        };
-        // Insert nesecary resources, events and systems
-        app.insert_resource(plugin)
-            .add_event::<WebcamFacialDataEvent>()
-            .add_systems(Update, webcam_facial_task_runner);
+// Every face candidate detector returned for one frame with thresholds it was judged by,
+// for tuning why face is picked or missed (config_diagnostics)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WebcamFacialDiagnostics {
+    pub candidates: Vec<DetectedFace>, // Full frame pixels, sorted by score descending, before score and area gates
+    pub score_thresh: f64, // Detector threshold, weaker candidates aren't returned by detector at all
+    pub emit_score: f64, // Score candidate needed to be reported (min emit, acquire or keep score while tracked)
+    pub min_track_box_area: u32, // Smaller candidates are ignored
+    pub timestamp: Duration, // Same as timestamp of data from this frame
+}
+
+// Sleep in short steps so stopping the task isn't delayed
+fn sleep_while_running(duration: Duration, task_running: &AtomicBool) {
+    let sleep_until = Instant::now() + duration;
+    while task_running.load(Ordering::SeqCst) {
+        let remaining = sleep_until.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(STOP_POLL_INTERVAL));
     }
 }
 
-impl Default for WebcamFacialPlugin {
-    fn default() -> Self {
-        Self {
-            config_webcam_device: 0,
-            config_webcam_width: 640,
-            config_webcam_height: 480,
-            config_webcam_framerate: 15,
-            config_webcam_autostart: true,
-            config_filter_type: SmoothingFilterType::LowPass(0.1),
-            config_filter_length: 10,
+// Send without blocking, if channel is full drop oldest data so newest always gets through
+fn send_latest<T>(
+    sender: &Sender<T>,
+    receiver: &Receiver<T>,
+    mut data: T,
+) -> Result<(), SendError<T>> {
+    loop {
+        match sender.try_send(data) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(returned)) => {
+                let _ = receiver.try_recv();
+                data = returned;
+            }
+            Err(TrySendError::Disconnected(returned)) => return Err(SendError(returned)),
         }
     }
 }
 
-fn webcam_facial_task_runner(
-    mut webcam_facial: ResMut<WebcamFacialController>,
-    mut commands: Commands,
-    mut plugin_task: Query<(Entity, &mut WebcamFacialTask)>,
-    mut plugin_events: EventWriter<WebcamFacialDataEvent>,
-) {
-    // If enabled and not running - start task
-    if webcam_facial.control & !webcam_facial.status.load(Ordering::SeqCst) {
-        // Get Arc clones
-        let task_running = webcam_facial.status.clone();
-        let sender_clone = webcam_facial.sender.clone();
+// Region of interest limited to frame, whole frame if not set or outside of frame
+fn clamp_roi(
+    roi: Option<(u32, u32, u32, u32)>,
+    frame_width: u32,
+    frame_height: u32,
+) -> (u32, u32, u32, u32) {
+    let Some((x, y, width, height)) = roi else {
+        return (0, 0, frame_width, frame_height);
+    };
+    let x = x.min(frame_width);
+    let y = y.min(frame_height);
+    let width = width.min(frame_width - x);
+    let height = height.min(frame_height - y);
+    if width == 0 || height == 0 {
+        return (0, 0, frame_width, frame_height);
+    }
+    (x, y, width, height)
+}
 
-        let camera_device = webcam_facial.config_device;
-        let camera_width = webcam_facial.config_width;
-        let camera_height = webcam_facial.config_height;
-        let camera_framerate = webcam_facial.config_framerate;
-        let filter_type = webcam_facial.config_filter_type;
-        let filter_length = webcam_facial.config_filter_length;
+// Detect faces in frame captured elsewhere (8-bit grayscale or RGB pixels row by row, without padding),
+// faces sorted by score in default CoordinateMode::CenterOriginYDown with typical webcam field of view
+pub fn detect_frame(
+    detector: &mut dyn FaceDetector,
+    rgb_or_gray: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<Vec<WebcamFacialData>, WebcamFacialError> {
+    let pixels = width as usize * height as usize;
+    let grayscale_image = match rgb_or_gray.len() {
+        _ if pixels == 0 => None,
+        len if len == pixels => GrayImage::from_raw(width, height, rgb_or_gray.to_vec()),
+        len if len == pixels * 3 => RgbImage::from_raw(width, height, rgb_or_gray.to_vec())
+            .map(|frame| CapturedFrame::Rgb(frame).luma_region((0, 0, width, height), Vec::new())),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        WebcamFacialError::MalformedFrame(format!(
+            "{} bytes isn't {}x{} grayscale or RGB frame",
+            rgb_or_gray.len(),
+            width,
+            height
+        ))
+    })?;
+    let mut faces = detector.detect(&grayscale_image);
+    faces.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(faces
+        .iter()
+        .map(|face| WebcamFacialData {
+            face_count: faces.len() as u32,
+            ..facial_data_from_face(
+                face,
+                width,
+                height,
+                WEBCAM_HORIZONTAL_FOV,
+                1.0,
+                REFERENCE_FACE_WIDTH_CM,
+            )
+        })
+        .collect())
+}
 
-        info!("Starting plugin");
-        let thread_pool = AsyncComputeTaskPool::get();
-        // Main task and its loop
-        let task = thread_pool.spawn(async move {
-            // Initialize webcam
-            let Some(mut cam_iter) = get_camera_frame_iterator(
-                camera_device,
-                camera_width,
-                camera_height,
-                camera_framerate,
-            ) else {
-                return false;
-            };
-            // Initialize face detector
-            //TODO Model selection, remove hardcoded
-            let mut detector = match rustface::create_detector("assets/NN_Models/seeta.bin") {
-                Ok(mut detector) => {
-                    info!("Using assets/NN_Models/seeta.bin recognition model.");
-                    detector.set_min_face_size(20);
-                    detector.set_score_thresh(2.0);
-                    detector.set_pyramid_scale_factor(0.8);
-                    detector.set_slide_window_step(4, 4);
-                    detector
-                }
-                Err(error) => {
-                    error!("Failed to create detector: {}", error.to_string());
-                    return false;
-                }
-            };
+// Neutral position becomes origin of center and normalized coordinates
+fn subtract_calibration(
+    mut data: WebcamFacialData,
+    (center_x, center_y, normalized_x, normalized_y): (f32, f32, f32, f32),
+) -> WebcamFacialData {
+    if data.face_found {
+        data.center_x -= center_x;
+        data.center_y -= center_y;
+        data.normalized_x -= normalized_x;
+        data.normalized_y -= normalized_y;
+    }
+    data
+}
 
-            let mut filtered_data = WebcamFacialDataFiltered::new(filter_length, filter_type);
-            info!("Capturing frames...");
-            while task_running.load(Ordering::SeqCst) {
-                // Get frame from buffer
-                let rgb_frame = cam_iter.next().unwrap();
-                // Convert RGB frame to grayscale
-                let grayscale_image = ImageBuffer::from_fn(camera_width, camera_height, |x, y| {
-                    let rgb_pixel = *rgb_frame.get_pixel(x, y);
-                    let gray_value = u32::from(rgb_pixel[0]) * 77
-                        + u32::from(rgb_pixel[1]) * 150
-                        + u32::from(rgb_pixel[2]) * 29;
-                    Luma([((gray_value >> 8) & 0xFF) as u8])
-                });
-                // Get Image data from buffer data
-                let grayscale_image_data =
-                    ImageData::new(&grayscale_image, camera_width, camera_height);
+// Sliding window steps packed for atomic storage
+const fn slide_window_bits((step_x, step_y): (u32, u32)) -> u64 {
+    (step_x as u64) << 32 | step_y as u64
+}
 
-                // Detect face data in provided image data
-                let faces = detector.detect(&grayscale_image_data);
+const fn slide_window_from_bits(bits: u64) -> (u32, u32) {
+    ((bits >> 32) as u32, bits as u32)
+}
 
-                // Initialize zero values if face not found
-                let mut facial_data = WebcamFacialData::default();
+// Detector from config_face_detector, rustface by default with model from memory or file
+fn create_detector(
+    face_detector: Option<FaceDetectorFactory>,
+    model: &DetectorModel,
+    min_face_size: u32,
+    score_thresh: f64,
+    pyramid_scale: f32,
+    slide_window: (u32, u32),
+) -> Result<Box<dyn FaceDetector>, WebcamFacialError> {
+    face_detector.map_or_else(
+        || {
+            model
+                .load(min_face_size, score_thresh, pyramid_scale, slide_window)
+                .map(|detector| Box::new(detector) as Box<dyn FaceDetector>)
+        },
+        |face_detector| face_detector(),
+    )
+}
 
-                // Get face with maximum human face probability (best candidate)
+// Detect faces in grayscale region of interest starting at roi_x, roi_y,
+// downscaled image is faster to search. Faces are in full frame pixels
+fn detect_faces(
+    detector: &mut dyn FaceDetector,
+    grayscale_image: &GrayImage,
+    (roi_x, roi_y): (u32, u32),
+    detection_scale: f32,
+) -> Vec<DetectedFace> {
+    let (roi_width, roi_height) = grayscale_image.dimensions();
+    let faces = if detection_scale < 1.0 {
+        let scaled_image = imageops::resize(
+            grayscale_image,
+            ((roi_width as f32 * detection_scale) as u32).max(1),
+            ((roi_height as f32 * detection_scale) as u32).max(1),
+            imageops::FilterType::Triangle,
+        );
+        let faces = detector.detect(&scaled_image);
+        scale_faces(faces, roi_width, roi_height, scaled_image.dimensions())
+    } else {
+        detector.detect(grayscale_image)
+    };
+    faces
+        .into_iter()
+        .map(|face| DetectedFace {
+            x: face.x + roi_x as i32,
+            y: face.y + roi_y as i32,
+            ..face
+        })
+        .collect()
+}
 
-                faces.iter().max_by_key(|p| p.score() as i32).map_or_else(
-                    || debug!("No faces found. Using default zero values."),
-                    |max_face| {
-                        debug!("Max score face: {:?}", max_face);
-                        // Take face rectangle coords and score
-                        facial_data.x = faces[0].bbox().x() as f32;
-                        facial_data.y = faces[0].bbox().y() as f32;
-                        facial_data.width = faces[0].bbox().width() as f32;
-                        facial_data.height = faces[0].bbox().height() as f32;
-                        facial_data.score = faces[0].score() as f32;
+// Map faces found in downscaled image back to original image pixels
+fn scale_faces(
+    faces: Vec<DetectedFace>,
+    width: u32,
+    height: u32,
+    (scaled_width, scaled_height): (u32, u32),
+) -> Vec<DetectedFace> {
+    // Exact ratio, scaled size was rounded
+    let scale_x = width as f32 / scaled_width as f32;
+    let scale_y = height as f32 / scaled_height as f32;
+    faces
+        .into_iter()
+        .map(|face| DetectedFace {
+            x: (face.x as f32 * scale_x).round() as i32,
+            y: (face.y as f32 * scale_y).round() as i32,
+            width: (face.width as f32 * scale_x).round() as u32,
+            height: (face.height as f32 * scale_y).round() as u32,
+            ..face
+        })
+        .collect()
+}
 
-                        // Calculate the scale factor to map the camera resolution
-                        let w_scale_factor = 100.0 / camera_width as f32;
-                        let h_scale_factor = 100.0 / camera_width as f32;
+// Map detected face rectangle to WebcamFacialData coordinates
+// fov - horizontal field of view in degrees, pixel_aspect - pixel width / height
+fn facial_data_from_face(
+    face: &DetectedFace,
+    camera_width: u32,
+    camera_height: u32,
+    fov: f32,
+    pixel_aspect: f32,
+    reference_face_width: f32,
+) -> WebcamFacialData {
+    // Take face rectangle coords and score
+    let mut facial_data = WebcamFacialData {
+        x: face.x as f32,
+        y: face.y as f32,
+        width: face.width as f32,
+        height: face.height as f32,
+        score: face.score as f32,
+        face_found: true,
+        ..Default::default()
+    };
 
-                        // Calculate the coordinates and dimensions in the desired range (-50.0) to (50.0)
-                        facial_data.x = facial_data.x.mul_add(w_scale_factor, -50.0);
-                        facial_data.y = facial_data.y.mul_add(h_scale_factor, -50.0);
-                        facial_data.width *= w_scale_factor;
-                        facial_data.height *= h_scale_factor;
-                        facial_data.center_x =
-                            2.0f32.mul_add(facial_data.x, facial_data.width) / -2.0; // minus flips values so negative is left
-                        facial_data.center_y =
-                            2.0f32.mul_add(facial_data.y, facial_data.height) / 2.0;
-                    },
-                );
-                filtered_data.push(facial_data);
+    // Face center in range -1.0 .. 1.0 and size in range 0.0 .. 1.0 of frame
+    let half_width = camera_width as f32 / 2.0;
+    let half_height = camera_height as f32 / 2.0;
+    facial_data.normalized_x =
+        (half_width - facial_data.width.mul_add(0.5, facial_data.x)) / half_width; // flipped same as center_x
+    facial_data.normalized_y =
+        (facial_data.height.mul_add(0.5, facial_data.y) - half_height) / half_height;
+    facial_data.normalized_width = facial_data.width / camera_width as f32;
+    facial_data.normalized_height = facial_data.height / camera_height as f32;
 
-                // Send processed and filtered data
-                match sender_clone.send(filtered_data.get()) {
-                    Ok(()) => debug!("Data from task sent."),
+    // Angles of face direction from camera axis using pinhole model with assumed field of view,
+    // same sign as normalized_x, pitch positive up. Face size cancels out (distance and offset scale together).
+    // Non-square pixels have different focal length in vertical pixels
+    let focal_length = half_width / (fov.to_radians() / 2.0).tan();
+    facial_data.yaw = (facial_data.normalized_x * half_width / focal_length).atan();
+    facial_data.pitch =
+        (-facial_data.normalized_y * half_height / (focal_length * pixel_aspect)).atan();
+    // Similar triangles of known face width, rough since face sizes and detector boxes vary
+    facial_data.distance_cm = reference_face_width * focal_length / facial_data.width.max(1.0);
 
-                    Err(SendError(data)) => error!("Failed to send task data: {:?}", data),
-                }
-            }
-            true
-        });
-        commands.spawn(WebcamFacialTask(task));
-        // Set flag that we started thread
-        webcam_facial.status.store(true, Ordering::SeqCst);
-    }
-    // If not enabled and task is running set flag to stop
-    if !webcam_facial.control & webcam_facial.status.load(Ordering::SeqCst) {
-        webcam_facial.status.store(false, Ordering::SeqCst);
-    }
-    for (entity, mut task) in &mut plugin_task {
-        if let Some(status) = future::block_on(future::poll_once(&mut task.0)) {
-            // Task completed, so remove task component from entity
-            commands.entity(entity).remove::<WebcamFacialTask>();
-            webcam_facial.status.store(false, Ordering::SeqCst);
-            webcam_facial.control = false;
-            if status {
-                info!("Camera stopped.");
-            } else {
-                info!("Plugin setup failed. Plugin self disabled.");
-            }
-        }
-    }
-    while let Ok(data) = webcam_facial.receiver.try_recv() {
-        debug!("Send Bevy event {:?}", data);
-        plugin_events.send(WebcamFacialDataEvent(data));
-    }
+    // Detector finds frontal faces, turned head gets narrower box or face far from camera axis
+    // is likely looking past the screen. Rough guess only, detector gives no eye landmarks
+    facial_data.area = (face.width * face.height) as i32;
+    facial_data.aspect_ratio = facial_data.width * pixel_aspect / facial_data.height.max(1.0);
+    facial_data.looking_at_screen = facial_data.yaw.abs() < LOOKING_MAX_ANGLE
+        && facial_data.pitch.abs() < LOOKING_MAX_ANGLE
+        && (LOOKING_ASPECT_RATIO.0..=LOOKING_ASPECT_RATIO.1).contains(&facial_data.aspect_ratio);
+
+    // Cut off face has box center away from real face center
+    facial_data.quality = WebcamFacialQuality {
+        touches_edge: face.x <= QUALITY_EDGE_MARGIN
+            || face.y <= QUALITY_EDGE_MARGIN
+            || face.x + face.width as i32 >= camera_width as i32 - QUALITY_EDGE_MARGIN
+            || face.y + face.height as i32 >= camera_height as i32 - QUALITY_EDGE_MARGIN,
+        normalized_score: (facial_data.score / QUALITY_FULL_SCORE).clamp(0.0, 1.0),
+        relative_size: (facial_data.normalized_width * facial_data.normalized_height).min(1.0),
+    };
+
+    // Calculate the scale factor to map the camera resolution, vertical in horizontal pixel units
+    let w_scale_factor = 100.0 / camera_width as f32;
+    let h_scale_factor = 100.0 / (camera_width as f32 * pixel_aspect);
+
+    // Calculate the coordinates and dimensions in the desired range (-50.0) to (50.0)
+    facial_data.x = facial_data.x.mul_add(w_scale_factor, -50.0);
+    facial_data.y = facial_data.y.mul_add(h_scale_factor, -50.0);
+    facial_data.width *= w_scale_factor;
+    facial_data.height *= h_scale_factor;
+    facial_data.center_x = 2.0f32.mul_add(facial_data.x, facial_data.width) / -2.0; // minus flips values so negative is left
+    facial_data.center_y = 2.0f32.mul_add(facial_data.y, facial_data.height) / 2.0;
+    facial_data
 }
 
-fn get_camera_frame_iterator(
-    camera_device: u32,
-    camera_width: u32,
-    camera_height: u32,
-    camera_framerate: u32,
-) -> Option<camera_capture::ImageIterator> {
-    // Create the camera device
-    let camera_device = match camera_capture::create(camera_device) {
-        Ok(device) => {
-            #[cfg(unix)]
-            info!("Using '/dev/video{}' camera.", camera_device);
-            #[cfg(windows)]
-            info!("Using camera ID:{}.", camera_device);
-            device
-        }
-        Err(err) => {
-            error!(
-                "Error creating camera device [{}]: {:?}",
-                camera_device, err
-            );
-            return None;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reports one face at known full resolution position, mapped to pixels of image it gets
+    struct ScaledFaceDetector(DetectedFace, (u32, u32));
+
+    impl FaceDetector for ScaledFaceDetector {
+        fn detect(&mut self, image: &GrayImage) -> Vec<DetectedFace> {
+            let (face, (width, height)) = (self.0, self.1);
+            let scale_x = image.width() as f32 / width as f32;
+            let scale_y = image.height() as f32 / height as f32;
+            vec![DetectedFace {
+                x: (face.x as f32 * scale_x).round() as i32,
+                y: (face.y as f32 * scale_y).round() as i32,
+                width: (face.width as f32 * scale_x).round() as u32,
+                height: (face.height as f32 * scale_y).round() as u32,
+                ..face
+            }]
         }
-    };
-    // Set the resolution
-    let resolution_device = match camera_device.resolution(camera_width, camera_height) {
-        Ok(resolution) => {
-            info!(
-                "Camera resolution set to {}x{}.",
-                camera_width, camera_height
+    }
+
+    fn assert_close(face: DetectedFace, expected: DetectedFace) {
+        for (value, expected_value) in [
+            (face.x, expected.x),
+            (face.y, expected.y),
+            (face.width as i32, expected.width as i32),
+            (face.height as i32, expected.height as i32),
+        ] {
+            assert!(
+                (value - expected_value).abs() <= 1,
+                "{face:?} not within 1 px of {expected:?}"
             );
-            resolution
-        }
-        Err(err) => {
-            error!("Error setting camera resolution: {:?}", err);
-            return None;
-        }
-    };
-    // Set the frame rate and start the camera capture
-    let cam_iter = match resolution_device.fps(f64::from(camera_framerate)) {
-        Ok(fps) => {
-            info!("Camera fps set to {}.", camera_framerate);
-            fps.start()
-        }
-        Err(err) => {
-            error!("Error setting camera frame rate: {:?}", err);
-            return None;
-        }
-    };
-    match cam_iter {
-        Ok(iter) => Some(iter),
-        Err(err) => {
-            error!("Error starting camera: {:?}", err);
-            None
         }
     }
+
+    #[test]
+    fn scaled_detection_maps_back_to_frame() {
+        let face = DetectedFace {
+            x: 1237,
+            y: 411,
+            width: 317,
+            height: 353,
+            score: 5.0,
+        };
+        let mut detector = ScaledFaceDetector(face, (1920, 1080));
+        let faces = detect_faces(&mut detector, &GrayImage::new(1920, 1080), (0, 0), 0.3);
+        assert_eq!(faces.len(), 1);
+        assert_close(faces[0], face);
+    }
+
+    #[test]
+    fn roi_offset_added_after_scaling() {
+        // 1333x777 region scales to 399.9x233.1, truncated to 399x233
+        let (roi_x, roi_y) = (101, 57);
+        let face = DetectedFace {
+            x: 853,
+            y: 379,
+            width: 251,
+            height: 263,
+            score: 5.0,
+        };
+        let mut detector = ScaledFaceDetector(face, (1333, 777));
+        let faces = detect_faces(
+            &mut detector,
+            &GrayImage::new(1333, 777),
+            (roi_x, roi_y),
+            0.3,
+        );
+        assert_eq!(faces.len(), 1);
+        assert_close(
+            faces[0],
+            DetectedFace {
+                x: face.x + roi_x as i32,
+                y: face.y + roi_y as i32,
+                ..face
+            },
+        );
+    }
 }
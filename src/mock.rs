@@ -0,0 +1,145 @@
+use bevy::ecs::system::{Query, Res, Resource};
+use std::{f32::consts::TAU, time::Instant};
+#[cfg(feature = "serde")]
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+#[cfg(feature = "serde")]
+use crate::WebcamFacialError;
+use crate::{
+    send_latest, WebcamFacialController, WebcamFacialData, WebcamFacialQuality,
+    WEBCAM_HORIZONTAL_FOV,
+};
+
+// Insert to emit scripted data instead of opening camera, script gets seconds since mock was created.
+// Data is sent as is every update while control is enabled (no filters, smoothing or deadzone),
+// every camera gets the same data with own camera_id
+#[derive(Resource)]
+pub struct WebcamFacialMock {
+    script: Box<dyn Fn(f32) -> WebcamFacialData + Send + Sync>,
+    started: Instant,
+}
+
+impl WebcamFacialMock {
+    pub fn new(script: impl Fn(f32) -> WebcamFacialData + Send + Sync + 'static) -> Self {
+        Self {
+            script: Box::new(script),
+            started: Instant::now(),
+        }
+    }
+
+    // Face sweeping left and right across frame center once per period seconds
+    pub fn sweep(period: f32) -> Self {
+        Self::new(move |elapsed| {
+            let position = (elapsed / period.max(f32::EPSILON) * TAU).sin();
+            WebcamFacialData {
+                center_x: position * 40.0,
+                x: -position.mul_add(40.0, 10.0),
+                y: -10.0,
+                width: 20.0,
+                height: 20.0,
+                score: 10.0,
+                face_found: true,
+                normalized_x: position * 0.8,
+                normalized_width: 0.2,
+                normalized_height: 0.27,
+                yaw: (position * 0.8 * (WEBCAM_HORIZONTAL_FOV.to_radians() / 2.0).tan()).atan(),
+                id: 1,
+                looking_at_screen: position.abs() < 0.5,
+                face_count: 1,
+                area: 16384, // 128x128 face in 640x480 frame
+                aspect_ratio: 1.0,
+                distance_cm: 65.0, // 128 pixel wide face with default field of view
+                quality: WebcamFacialQuality {
+                    touches_edge: false,
+                    normalized_score: 0.5,
+                    relative_size: 0.05,
+                },
+                ..Default::default()
+            }
+        })
+    }
+}
+
+impl WebcamFacialMock {
+    // Replay recorded data at original timing (by timestamp), last record is held after the end
+    pub fn replay(mut records: Vec<WebcamFacialData>) -> Self {
+        records.sort_by_key(|record| record.timestamp);
+        Self::new(move |elapsed| {
+            let played =
+                records.partition_point(|record| record.timestamp.as_secs_f32() <= elapsed);
+            played
+                .checked_sub(1)
+                .map_or_else(WebcamFacialData::default, |index| records[index])
+        })
+    }
+
+    // Replay file with one JSON serialized WebcamFacialData per line
+    #[cfg(feature = "serde")]
+    pub fn replay_file(path: impl AsRef<Path>) -> Result<Self, WebcamFacialError> {
+        let path = path.as_ref();
+        let replay_error = |err: &dyn std::fmt::Display| {
+            WebcamFacialError::CameraOpen(format!("'{}': {}", path.display(), err))
+        };
+        let file = File::open(path).map_err(|err| replay_error(&err))?;
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|err| replay_error(&err))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            records.push(serde_json::from_str(&line).map_err(|err| replay_error(&err))?);
+        }
+        Ok(Self::replay(records))
+    }
+}
+
+// Feeds scripted data to the same channels as capture task, so task runner sends all events
+pub fn webcam_facial_mock_updater(
+    controllers: Query<&WebcamFacialController>,
+    mock: Res<WebcamFacialMock>,
+) {
+    let elapsed = mock.started.elapsed();
+    let script_data = (mock.script)(elapsed.as_secs_f32());
+    for webcam_facial in controllers.iter().filter(|controller| controller.control) {
+        let data = WebcamFacialData {
+            timestamp: elapsed,
+            camera_id: webcam_facial.camera_id(),
+            ..script_data
+        };
+        let all_data = if data.face_found {
+            vec![data]
+        } else {
+            Vec::new()
+        };
+        let engine = webcam_facial.engine();
+        let _ = send_latest(&engine.multi_sender, &engine.multi_receiver, all_data);
+        let _ = send_latest(&engine.sender, &engine.receiver, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_positions() {
+        let mock = WebcamFacialMock::sweep(4.0);
+        let at = |seconds: f32| (mock.script)(seconds);
+        // Center, right, center, left quarter periods apart
+        for (seconds, position) in [(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, -1.0), (4.0, 0.0)] {
+            let data = at(seconds);
+            assert!(data.face_found);
+            assert!((data.center_x - position * 40.0).abs() < 1e-3);
+            assert!((data.normalized_x - position * 0.8).abs() < 1e-3);
+            assert!((data.x + position.mul_add(40.0, 10.0)).abs() < 1e-3);
+            assert_eq!(data.looking_at_screen, position == 0.0);
+            assert!(data.yaw * position >= 0.0);
+        }
+        // Box stays inside frame at the extremes
+        assert!(at(1.0).x >= -50.0 && at(3.0).x + at(3.0).width <= 50.0);
+    }
+}
@@ -0,0 +1,157 @@
+use log::trace;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::{WebcamFacialData, WebcamFacialError};
+
+// OSC address of face messages
+const OSC_ADDRESS: &str = "/webcam_facial/face";
+
+// Encoding of data sent by network sink (config_network_format)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NetworkFormat {
+    #[default]
+    Json, // One serialized WebcamFacialData per datagram
+    Osc, // OSC message /webcam_facial/face with camera_id, id, face_found, face_count as ints, then floats
+}
+
+// UDP socket sending processed data of capture task to other programs
+pub(crate) struct NetworkSink {
+    socket: UdpSocket,
+    format: NetworkFormat,
+    buffer: Vec<u8>,
+}
+
+impl NetworkSink {
+    // address - ex. "127.0.0.1:9000" or "[::1]:9000", datagrams are sent from any free local port
+    pub(crate) fn open(address: &str, format: NetworkFormat) -> Result<Self, WebcamFacialError> {
+        let network_error =
+            |err: std::io::Error| WebcamFacialError::NetworkSink(format!("'{}': {}", address, err));
+        let target = address
+            .to_socket_addrs()
+            .map_err(network_error)?
+            .next()
+            .ok_or_else(|| {
+                WebcamFacialError::NetworkSink(format!("'{}': no address resolved", address))
+            })?;
+        // Local socket must be of same address family as target
+        let local = match target {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let socket = UdpSocket::bind(local).map_err(network_error)?;
+        socket.connect(target).map_err(network_error)?;
+        Ok(Self {
+            socket,
+            format,
+            buffer: Vec::new(),
+        })
+    }
+
+    // Nobody listening isn't an error, datagrams are just lost
+    pub(crate) fn send(&mut self, data: &WebcamFacialData) {
+        self.buffer.clear();
+        match self.format {
+            NetworkFormat::Json => {
+                if let Err(err) = serde_json::to_writer(&mut self.buffer, data) {
+                    trace!("Network sink serialization failed: {}", err);
+                    return;
+                }
+            }
+            NetworkFormat::Osc => osc_message(&mut self.buffer, data),
+        }
+        if let Err(err) = self.socket.send(&self.buffer) {
+            trace!("Network sink send failed: {}", err);
+        }
+    }
+}
+
+// OSC 1.0 message, strings are null terminated and padded to 4 bytes, numbers big-endian
+fn osc_message(buffer: &mut Vec<u8>, data: &WebcamFacialData) {
+    let floats = [
+        data.center_x,
+        data.center_y,
+        data.normalized_x,
+        data.normalized_y,
+        data.normalized_width,
+        data.normalized_height,
+        data.yaw,
+        data.pitch,
+        data.score,
+        data.distance_cm,
+    ];
+    let ints = [
+        data.camera_id as i32,
+        data.id as i32,
+        i32::from(data.face_found),
+        data.face_count as i32,
+    ];
+    osc_string(buffer, OSC_ADDRESS);
+    osc_string(
+        buffer,
+        &format!(",{}{}", "i".repeat(ints.len()), "f".repeat(floats.len())),
+    );
+    for value in ints {
+        buffer.extend_from_slice(&value.to_be_bytes());
+    }
+    for value in floats {
+        buffer.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn osc_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(value.as_bytes());
+    // At least one null, up to 4 byte boundary
+    buffer.resize((buffer.len() / 4 + 1) * 4, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc_string_padding() {
+        let mut buffer = Vec::new();
+        osc_string(&mut buffer, "abc");
+        assert_eq!(buffer, b"abc\0");
+        buffer.clear();
+        osc_string(&mut buffer, "abcd");
+        assert_eq!(buffer, b"abcd\0\0\0\0");
+    }
+
+    #[test]
+    fn osc_message_bytes() {
+        let data = WebcamFacialData {
+            camera_id: 1,
+            id: 2,
+            face_found: true,
+            face_count: 3,
+            center_x: 0.5,
+            score: 1.0,
+            distance_cm: -2.0,
+            ..Default::default()
+        };
+        let mut buffer = Vec::new();
+        osc_message(&mut buffer, &data);
+        let mut expected = b"/webcam_facial/face\0,iiiiffffffffff\0".to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 3]);
+        expected.extend_from_slice(&[0x3f, 0, 0, 0]); // center_x
+        expected.extend_from_slice(&[0; 7 * 4]);
+        expected.extend_from_slice(&[0x3f, 0x80, 0, 0]); // score
+        expected.extend_from_slice(&[0xc0, 0, 0, 0]); // distance_cm
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn ipv6_target() {
+        let Ok(receiver) = UdpSocket::bind("[::1]:0") else {
+            return; // No IPv6 loopback on this machine
+        };
+        let address = receiver.local_addr().unwrap().to_string();
+        let mut sink = NetworkSink::open(&address, NetworkFormat::Osc).unwrap();
+        sink.send(&WebcamFacialData::default());
+        let mut buffer = [0; 128];
+        let len = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..4], b"/web");
+        assert_eq!(len, 92);
+    }
+}
@@ -0,0 +1,1062 @@
+#[cfg(feature = "frame_preview")]
+use bevy::render::color::Color;
+use bevy::{
+    app::{App, AppExit, Last, Plugin, Update},
+    ecs::{
+        component::Component,
+        event::{Event, EventReader, EventWriter},
+        query::With,
+        schedule::{common_conditions::resource_exists, IntoSystemConfigs, SystemSet},
+        system::{Query, Res, SystemParam},
+    },
+    log::{error, info, trace, warn},
+    math::{UVec2, Vec2},
+    window::{PrimaryWindow, Window},
+};
+use std::{mem, time::Duration};
+
+#[cfg(feature = "frame_preview")]
+use crate::preview;
+#[cfg(feature = "network")]
+use crate::NetworkFormat;
+#[cfg(feature = "blink")]
+use crate::WebcamFacialEyes;
+#[cfg(feature = "cursor")]
+use crate::{cursor, WebcamFacialCursor};
+use crate::{
+    interpolation, mock, ActiveCameraConfig, CoordinateMode, DetectorPreset, FaceDetectorFactory,
+    FaceSelection, FrameRotation, SmoothingFilterType, WebcamControl, WebcamFacialConfig,
+    WebcamFacialData, WebcamFacialDiagnostics, WebcamFacialEngine, WebcamFacialError,
+    WebcamFacialInterpolated, WebcamFacialMock, WebcamFacialSource, WebcamFacialStats,
+    WebcamFormat,
+};
+
+// Largest difference of data fields treated as no change (config_emit_on_change_only)
+const EMIT_CHANGE_EPSILON: f32 = 0.001;
+
+pub struct WebcamFacialPlugin {
+    pub config_camera_id: u32,
+    pub config_frame_source: WebcamFacialSource,
+    pub config_webcam_device: u32,
+    pub config_webcam_device_id: Option<String>,
+    pub config_webcam_width: u32,
+    pub config_webcam_height: u32,
+    pub config_webcam_resolutions: Vec<(u32, u32)>,
+    pub config_webcam_framerate: u32,
+    pub config_webcam_autostart: bool,
+    pub config_webcam_format: WebcamFormat,
+    pub config_webcam_timeout: Duration,
+    pub config_webcam_reconnect_interval: Duration,
+    pub config_warmup_frames: u32,
+    pub config_webcam_nearest_mode: bool,
+    pub config_flip_horizontal: bool,
+    pub config_flip_vertical: bool,
+    pub config_rotation: FrameRotation,
+    pub config_webcam_fov: f32,
+    pub config_pixel_aspect: f32,
+    pub config_reference_face_width_cm: f32,
+    pub config_detection_interval: u32,
+    pub config_min_frame_interval: Duration,
+    pub config_channel_capacity: usize,
+    pub config_max_staleness: Duration,
+    pub config_emit_on_change_only: bool,
+    #[cfg(feature = "network")]
+    pub config_network_address: Option<String>,
+    #[cfg(feature = "network")]
+    pub config_network_format: NetworkFormat,
+    #[cfg(feature = "frame_preview")]
+    pub config_expose_frame: bool,
+    #[cfg(feature = "frame_preview")]
+    pub config_overlay_color: Option<Color>,
+    #[cfg(feature = "frame_preview")]
+    pub config_overlay_thickness: u32,
+    #[cfg(feature = "frame_preview")]
+    pub config_preview_mirror: bool,
+    pub config_filter_type: SmoothingFilterType,
+    pub config_filter_length: u32,
+    pub config_smoothing: f32,
+    pub config_size_smoothing: Option<f32>,
+    pub config_deadzone_x: f32,
+    pub config_deadzone_y: f32,
+    pub config_sensitivity_x: f32,
+    pub config_sensitivity_y: f32,
+    pub config_coordinate_mode: CoordinateMode,
+    pub config_window_coordinates: bool,
+    pub config_pause_on_focus_loss: bool, // Pause while primary window is unfocused
+    pub config_interpolation: bool, // WebcamFacialInterpolated resource for first plugin instance camera
+    #[cfg(feature = "cursor")]
+    pub config_cursor_sensitivity: f32,
+    #[cfg(feature = "cursor")]
+    pub config_cursor_invert: (bool, bool),
+    pub config_tracking_distance: f32,
+    pub config_detection_scale: f32,
+    pub config_roi: Option<(u32, u32, u32, u32)>,
+    pub config_min_face_size: u32,
+    pub config_score_thresh: f64,
+    pub config_min_emit_score: f32,
+    pub config_acquire_score: f32,
+    pub config_keep_score: f32,
+    pub config_min_track_box_area: u32,
+    pub config_diagnostics: bool, // WebcamFacialDiagnosticsEvent with all detector candidates of every frame
+    pub config_max_faces: usize,
+    pub config_face_selection: FaceSelection,
+    pub config_lost_grace_frames: u32,
+    pub config_hold_last_on_dropout: bool,
+    pub config_hold_max_duration: Duration,
+    #[cfg(feature = "blink")]
+    pub config_blink_threshold: f32,
+    pub config_pyramid_scale: f32,
+    pub config_slide_window: (u32, u32),
+    pub config_model_path: String,
+    pub config_model_bytes: Option<&'static [u8]>,
+    pub config_model_checksum: Option<u64>,
+    pub config_face_detector: Option<FaceDetectorFactory>,
+}
+// Plugin configuration for webcam to be accesible from plugin system, wraps capture engine of one camera.
+// One entity with controller, WebcamFacialStats (and WebcamFacialFrame) per plugin instance
+#[derive(Component)]
+pub struct WebcamFacialController {
+    pub control: bool,
+    engine: WebcamFacialEngine,
+    config_emit_on_change_only: bool,
+    config_window_coordinates: bool,
+    config_pause_on_focus_loss: bool,
+    last_face_data: Option<WebcamFacialData>,
+    last_emitted_data: Option<WebcamFacialData>, // Last sent data, compared with new data in config_emit_on_change_only
+    window_size: Option<Vec2>, // Primary window size read every update (config_window_coordinates)
+    focus_paused: bool, // Paused by runner because window lost focus (config_pause_on_focus_loss)
+    restart: bool,
+    autostart_check: bool, // Missing webcam at first autostart turns control off instead of failing capture
+}
+
+// Plugin systems in Update (task runner sending events, mock and preview updaters),
+// order own systems with .after(WebcamFacialSet) to read events in the same frame
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WebcamFacialSet;
+
+// Despawned controller stops its capture without blocking, which releases camera
+impl Drop for WebcamFacialController {
+    fn drop(&mut self) {
+        self.engine.detach();
+    }
+}
+
+// WebcamFacialEvent event for sending WebcamFacialData to main Bevy app
+#[derive(Event)]
+pub struct WebcamFacialDataEvent(pub WebcamFacialData);
+
+// WebcamFacialErrorEvent event for reporting task failures to main Bevy app, with camera id
+#[derive(Event)]
+pub struct WebcamFacialErrorEvent(pub WebcamFacialError, pub u32);
+
+// WebcamFacialMultiFaceEvent event with all faces detected in frame sorted by score descending,
+// with camera id (list is empty without faces)
+#[derive(Event)]
+pub struct WebcamFacialMultiFaceEvent(pub Vec<WebcamFacialData>, pub u32);
+
+// WebcamFacialDiagnosticsEvent event with all detector candidates of one frame (config_diagnostics), with camera id
+#[derive(Event)]
+pub struct WebcamFacialDiagnosticsEvent(pub WebcamFacialDiagnostics, pub u32);
+
+// Sent once when a face appears after frames without a face
+#[derive(Event)]
+pub struct WebcamFacialFaceAcquiredEvent(pub WebcamFacialData);
+
+// Sent once when the face disappears, carries last known face data
+#[derive(Event)]
+pub struct WebcamFacialFaceLostEvent(pub WebcamFacialData);
+
+// Sent when camera and detector are ready and first frame was captured, carries camera id
+#[derive(Event)]
+pub struct WebcamFacialStartedEvent(pub u32);
+
+// Sent with started event, resolution camera delivers (before config_rotation) and camera id
+#[derive(Event)]
+pub struct WebcamFacialResolutionEvent(pub UVec2, pub u32);
+
+// Sent when capture task finished (stopped, failed to start or failed), carries camera id
+#[derive(Event)]
+pub struct WebcamFacialStoppedEvent(pub u32);
+
+// Sent instead of starting capture when autostart finds no webcam device (Linux only), carries camera id.
+// Control is turned off, app runs without tracking
+#[derive(Event)]
+pub struct WebcamFacialNoCameraAvailableEvent(pub u32);
+
+// Sent when eye closure of the tracked face changes (only with 'blink' feature), carries camera id
+#[cfg(feature = "blink")]
+#[derive(Event)]
+pub struct WebcamFacialBlinkEvent(pub WebcamFacialEyes, pub u32);
+
+impl Plugin for WebcamFacialPlugin {
+    fn build(&self, app: &mut App) {
+        let plugin = self.controller();
+        // Every plugin instance runs own camera, controllers are told apart by camera id
+        let mut controllers = app.world.query::<&WebcamFacialController>();
+        if controllers
+            .iter(&app.world)
+            .any(|controller| controller.camera_id() == self.config_camera_id)
+        {
+            warn!(
+                "Camera id {} is used by several plugin instances.",
+                self.config_camera_id
+            );
+        }
+        app.world.spawn((plugin, WebcamFacialStats::default()));
+        // Events and systems are shared by all instances
+        if app.is_plugin_added::<Self>() {
+            return;
+        }
+        // Insert nesecary events and systems
+        app.add_event::<WebcamFacialDataEvent>()
+            .add_event::<WebcamFacialMultiFaceEvent>()
+            .add_event::<WebcamFacialDiagnosticsEvent>()
+            .add_event::<WebcamFacialErrorEvent>()
+            .add_event::<WebcamFacialFaceAcquiredEvent>()
+            .add_event::<WebcamFacialFaceLostEvent>()
+            .add_event::<WebcamFacialStartedEvent>()
+            .add_event::<WebcamFacialResolutionEvent>()
+            .add_event::<WebcamFacialStoppedEvent>()
+            .add_event::<WebcamFacialNoCameraAvailableEvent>()
+            .add_systems(
+                Update,
+                (
+                    mock::webcam_facial_mock_updater
+                        .run_if(resource_exists::<WebcamFacialMock>)
+                        .before(webcam_facial_task_runner),
+                    webcam_facial_task_runner,
+                )
+                    .in_set(WebcamFacialSet)
+                    .run_if(webcam_facial_active),
+            )
+            .add_systems(
+                Last,
+                webcam_facial_exit_cleanup.run_if(webcam_facial_active),
+            );
+        #[cfg(feature = "blink")]
+        app.add_event::<WebcamFacialBlinkEvent>();
+        // Interpolates camera of the first plugin instance
+        if self.config_interpolation {
+            app.insert_resource(WebcamFacialInterpolated::new(self.config_camera_id))
+                .add_systems(
+                    Update,
+                    interpolation::webcam_facial_interpolation_updater
+                        .after(webcam_facial_task_runner)
+                        .in_set(WebcamFacialSet)
+                        .run_if(webcam_facial_active),
+                );
+        }
+        // Cursor follows camera of the first plugin instance
+        #[cfg(feature = "cursor")]
+        app.insert_resource(WebcamFacialCursor {
+            position: bevy::math::Vec2::ZERO,
+            face_found: false,
+            sensitivity: self.config_cursor_sensitivity,
+            invert_x: self.config_cursor_invert.0,
+            invert_y: self.config_cursor_invert.1,
+            camera_id: self.config_camera_id,
+        })
+        .add_systems(
+            Update,
+            cursor::webcam_facial_cursor_updater
+                .after(webcam_facial_task_runner)
+                .in_set(WebcamFacialSet)
+                .run_if(webcam_facial_active),
+        );
+        #[cfg(feature = "frame_preview")]
+        app.add_systems(bevy::app::PreStartup, preview::webcam_facial_frame_setup)
+            .add_systems(
+                Update,
+                preview::webcam_facial_frame_updater
+                    .after(webcam_facial_task_runner)
+                    .in_set(WebcamFacialSet)
+                    .run_if(webcam_facial_active),
+            );
+    }
+
+    fn is_unique(&self) -> bool {
+        false
+    }
+}
+
+impl Default for WebcamFacialPlugin {
+    fn default() -> Self {
+        // Capture and detection defaults are shared with engine
+        let config = WebcamFacialConfig::default();
+        Self {
+            config_camera_id: config.config_camera_id,
+            config_frame_source: config.config_frame_source,
+            config_webcam_device: config.config_webcam_device,
+            config_webcam_device_id: config.config_webcam_device_id,
+            config_webcam_width: config.config_webcam_width,
+            config_webcam_height: config.config_webcam_height,
+            config_webcam_resolutions: config.config_webcam_resolutions,
+            config_webcam_framerate: config.config_webcam_framerate,
+            config_webcam_autostart: true,
+            config_webcam_format: config.config_webcam_format,
+            config_webcam_timeout: config.config_webcam_timeout,
+            config_webcam_reconnect_interval: config.config_webcam_reconnect_interval,
+            config_warmup_frames: config.config_warmup_frames,
+            config_webcam_nearest_mode: config.config_webcam_nearest_mode,
+            config_flip_horizontal: config.config_flip_horizontal,
+            config_flip_vertical: config.config_flip_vertical,
+            config_rotation: config.config_rotation,
+            config_webcam_fov: config.config_webcam_fov,
+            config_pixel_aspect: config.config_pixel_aspect,
+            config_reference_face_width_cm: config.config_reference_face_width_cm,
+            config_detection_interval: config.config_detection_interval,
+            config_min_frame_interval: config.config_min_frame_interval,
+            config_channel_capacity: config.config_channel_capacity,
+            config_max_staleness: config.config_max_staleness,
+            config_emit_on_change_only: false,
+            #[cfg(feature = "network")]
+            config_network_address: config.config_network_address,
+            #[cfg(feature = "network")]
+            config_network_format: config.config_network_format,
+            #[cfg(feature = "frame_preview")]
+            config_expose_frame: config.config_expose_frame,
+            #[cfg(feature = "frame_preview")]
+            config_overlay_color: None,
+            #[cfg(feature = "frame_preview")]
+            config_overlay_thickness: config.config_overlay_thickness,
+            #[cfg(feature = "frame_preview")]
+            config_preview_mirror: config.config_preview_mirror,
+            config_filter_type: config.config_filter_type,
+            config_filter_length: config.config_filter_length,
+            config_smoothing: config.config_smoothing,
+            config_size_smoothing: config.config_size_smoothing,
+            config_deadzone_x: config.config_deadzone_x,
+            config_deadzone_y: config.config_deadzone_y,
+            config_sensitivity_x: config.config_sensitivity_x,
+            config_sensitivity_y: config.config_sensitivity_y,
+            config_coordinate_mode: config.config_coordinate_mode,
+            config_window_coordinates: false,
+            config_pause_on_focus_loss: false,
+            config_interpolation: false,
+            #[cfg(feature = "cursor")]
+            config_cursor_sensitivity: 1.5,
+            #[cfg(feature = "cursor")]
+            config_cursor_invert: (false, false),
+            config_tracking_distance: config.config_tracking_distance,
+            config_detection_scale: config.config_detection_scale,
+            config_roi: config.config_roi,
+            config_min_face_size: config.config_min_face_size,
+            config_score_thresh: config.config_score_thresh,
+            config_min_emit_score: config.config_min_emit_score,
+            config_acquire_score: config.config_acquire_score,
+            config_keep_score: config.config_keep_score,
+            config_min_track_box_area: config.config_min_track_box_area,
+            config_diagnostics: config.config_diagnostics,
+            config_max_faces: config.config_max_faces,
+            config_face_selection: config.config_face_selection,
+            config_lost_grace_frames: config.config_lost_grace_frames,
+            config_hold_last_on_dropout: config.config_hold_last_on_dropout,
+            config_hold_max_duration: config.config_hold_max_duration,
+            #[cfg(feature = "blink")]
+            config_blink_threshold: config.config_blink_threshold,
+            config_pyramid_scale: config.config_pyramid_scale,
+            config_slide_window: config.config_slide_window,
+            config_model_path: config.config_model_path,
+            config_model_bytes: config.config_model_bytes,
+            config_model_checksum: config.config_model_checksum,
+            config_face_detector: config.config_face_detector,
+        }
+    }
+}
+
+// Builder on top of default settings, ex. WebcamFacialPlugin::default().with_device(1).with_resolution(1280, 720)
+impl WebcamFacialPlugin {
+    pub fn with_camera_id(mut self, camera_id: u32) -> Self {
+        self.config_camera_id = camera_id;
+        self
+    }
+
+    pub fn with_frame_source(mut self, frame_source: WebcamFacialSource) -> Self {
+        self.config_frame_source = frame_source;
+        self
+    }
+
+    pub fn with_device(mut self, device: u32) -> Self {
+        self.config_webcam_device = device;
+        self
+    }
+
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.config_webcam_device_id = Some(device_id.into());
+        self
+    }
+
+    pub fn with_resolution(mut self, width: u32, height: u32) -> Self {
+        self.config_webcam_width = width;
+        self.config_webcam_height = height;
+        self
+    }
+
+    pub fn with_framerate(mut self, framerate: u32) -> Self {
+        self.config_webcam_framerate = framerate;
+        self
+    }
+
+    pub fn with_autostart(mut self, autostart: bool) -> Self {
+        self.config_webcam_autostart = autostart;
+        self
+    }
+
+    pub fn with_format(mut self, format: WebcamFormat) -> Self {
+        self.config_webcam_format = format;
+        self
+    }
+
+    pub fn with_flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.config_flip_horizontal = horizontal;
+        self.config_flip_vertical = vertical;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: FrameRotation) -> Self {
+        self.config_rotation = rotation;
+        self
+    }
+
+    pub fn with_filter(mut self, filter_type: SmoothingFilterType, length: u32) -> Self {
+        self.config_filter_type = filter_type;
+        self.config_filter_length = length;
+        self
+    }
+
+    // Detector settings of preset, can be switched at runtime by controller set_preset()
+    pub fn with_preset(mut self, preset: DetectorPreset) -> Self {
+        let params = preset.params();
+        self.config_min_face_size = params.min_face_size;
+        self.config_score_thresh = params.score_thresh;
+        self.config_pyramid_scale = params.pyramid_scale;
+        self.config_slide_window = params.slide_window;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.config_smoothing = smoothing;
+        self
+    }
+
+    pub fn with_deadzone(mut self, deadzone_x: f32, deadzone_y: f32) -> Self {
+        self.config_deadzone_x = deadzone_x;
+        self.config_deadzone_y = deadzone_y;
+        self
+    }
+
+    pub fn with_sensitivity(mut self, sensitivity_x: f32, sensitivity_y: f32) -> Self {
+        self.config_sensitivity_x = sensitivity_x;
+        self.config_sensitivity_y = sensitivity_y;
+        self
+    }
+
+    pub fn with_coordinate_mode(mut self, coordinate_mode: CoordinateMode) -> Self {
+        self.config_coordinate_mode = coordinate_mode;
+        self
+    }
+
+    pub fn with_roi(mut self, roi: Option<(u32, u32, u32, u32)>) -> Self {
+        self.config_roi = roi;
+        self
+    }
+
+    pub fn with_model_path(mut self, model_path: impl Into<String>) -> Self {
+        self.config_model_path = model_path.into();
+        self
+    }
+
+    pub fn with_model_bytes(mut self, model: &'static [u8]) -> Self {
+        self.config_model_bytes = Some(model);
+        self
+    }
+
+    pub fn with_face_detector(mut self, face_detector: FaceDetectorFactory) -> Self {
+        self.config_face_detector = Some(face_detector);
+        self
+    }
+
+    // Frame source and detector checked up front, since Plugin::build can't fail.
+    // Setup errors can be handled before app.run() instead of failing capture later
+    pub fn validate(&self) -> Result<(), WebcamFacialError> {
+        WebcamFacialEngine::new(&self.engine_config()).validate()
+    }
+
+    // Capture and detection settings of this plugin, ex. for WebcamFacialEngine outside of Bevy App
+    pub fn engine_config(&self) -> WebcamFacialConfig {
+        WebcamFacialConfig {
+            config_camera_id: self.config_camera_id,
+            config_frame_source: self.config_frame_source.clone(),
+            config_webcam_device: self.config_webcam_device,
+            config_webcam_device_id: self.config_webcam_device_id.clone(),
+            config_webcam_width: self.config_webcam_width,
+            config_webcam_height: self.config_webcam_height,
+            config_webcam_resolutions: self.config_webcam_resolutions.clone(),
+            config_webcam_framerate: self.config_webcam_framerate,
+            config_webcam_format: self.config_webcam_format,
+            config_webcam_timeout: self.config_webcam_timeout,
+            config_webcam_reconnect_interval: self.config_webcam_reconnect_interval,
+            config_warmup_frames: self.config_warmup_frames,
+            config_webcam_nearest_mode: self.config_webcam_nearest_mode,
+            config_flip_horizontal: self.config_flip_horizontal,
+            config_flip_vertical: self.config_flip_vertical,
+            config_rotation: self.config_rotation,
+            config_webcam_fov: self.config_webcam_fov,
+            config_pixel_aspect: self.config_pixel_aspect,
+            config_reference_face_width_cm: self.config_reference_face_width_cm,
+            config_detection_interval: self.config_detection_interval,
+            config_min_frame_interval: self.config_min_frame_interval,
+            config_channel_capacity: self.config_channel_capacity,
+            config_max_staleness: self.config_max_staleness,
+            #[cfg(feature = "network")]
+            config_network_address: self.config_network_address.clone(),
+            #[cfg(feature = "network")]
+            config_network_format: self.config_network_format,
+            #[cfg(feature = "frame_preview")]
+            config_expose_frame: self.config_expose_frame,
+            #[cfg(feature = "frame_preview")]
+            config_overlay_color: self.config_overlay_color.map(|color| color.as_rgba_u8()),
+            #[cfg(feature = "frame_preview")]
+            config_overlay_thickness: self.config_overlay_thickness,
+            #[cfg(feature = "frame_preview")]
+            config_preview_mirror: self.config_preview_mirror,
+            config_filter_type: self.config_filter_type,
+            config_filter_length: self.config_filter_length,
+            config_smoothing: self.config_smoothing,
+            config_size_smoothing: self.config_size_smoothing,
+            config_deadzone_x: self.config_deadzone_x,
+            config_deadzone_y: self.config_deadzone_y,
+            config_sensitivity_x: self.config_sensitivity_x,
+            config_sensitivity_y: self.config_sensitivity_y,
+            config_coordinate_mode: self.config_coordinate_mode,
+            config_tracking_distance: self.config_tracking_distance,
+            config_detection_scale: self.config_detection_scale,
+            config_roi: self.config_roi,
+            config_min_face_size: self.config_min_face_size,
+            config_score_thresh: self.config_score_thresh,
+            config_min_emit_score: self.config_min_emit_score,
+            config_acquire_score: self.config_acquire_score,
+            config_keep_score: self.config_keep_score,
+            config_min_track_box_area: self.config_min_track_box_area,
+            config_diagnostics: self.config_diagnostics,
+            config_max_faces: self.config_max_faces,
+            config_face_selection: self.config_face_selection,
+            config_lost_grace_frames: self.config_lost_grace_frames,
+            config_hold_last_on_dropout: self.config_hold_last_on_dropout,
+            config_hold_max_duration: self.config_hold_max_duration,
+            #[cfg(feature = "blink")]
+            config_blink_threshold: self.config_blink_threshold,
+            config_pyramid_scale: self.config_pyramid_scale,
+            config_slide_window: self.config_slide_window,
+            config_model_path: self.config_model_path.clone(),
+            config_model_bytes: self.config_model_bytes,
+            config_model_checksum: self.config_model_checksum,
+            config_face_detector: self.config_face_detector.clone(),
+        }
+    }
+
+    // Controller with engine of this config, spawned as component by plugin
+    pub(crate) fn controller(&self) -> WebcamFacialController {
+        WebcamFacialController {
+            control: self.config_webcam_autostart,
+            engine: WebcamFacialEngine::new(&self.engine_config()),
+            config_emit_on_change_only: self.config_emit_on_change_only,
+            config_window_coordinates: self.config_window_coordinates,
+            config_pause_on_focus_loss: self.config_pause_on_focus_loss,
+            last_face_data: None,
+            last_emitted_data: None,
+            window_size: None,
+            focus_paused: false,
+            restart: false,
+            autostart_check: self.config_webcam_autostart,
+        }
+    }
+}
+
+// Run condition, true while capture of any camera is enabled or its task is still running/stopping
+pub fn webcam_facial_active(controllers: Query<&WebcamFacialController>) -> bool {
+    controllers
+        .iter()
+        .any(|webcam_facial| webcam_facial.control || webcam_facial.engine.has_thread())
+}
+
+// All events sent by task runner
+#[derive(SystemParam)]
+struct WebcamFacialEventWriters<'w> {
+    data: EventWriter<'w, WebcamFacialDataEvent>,
+    multi_face: EventWriter<'w, WebcamFacialMultiFaceEvent>,
+    diagnostics: EventWriter<'w, WebcamFacialDiagnosticsEvent>,
+    error: EventWriter<'w, WebcamFacialErrorEvent>,
+    face_acquired: EventWriter<'w, WebcamFacialFaceAcquiredEvent>,
+    face_lost: EventWriter<'w, WebcamFacialFaceLostEvent>,
+    started: EventWriter<'w, WebcamFacialStartedEvent>,
+    resolution: EventWriter<'w, WebcamFacialResolutionEvent>,
+    stopped: EventWriter<'w, WebcamFacialStoppedEvent>,
+    no_camera: EventWriter<'w, WebcamFacialNoCameraAvailableEvent>,
+    #[cfg(feature = "blink")]
+    blink: EventWriter<'w, WebcamFacialBlinkEvent>,
+}
+
+impl WebcamFacialController {
+    // Capture engine of this camera, settings and state shared with engine API
+    pub const fn engine(&self) -> &WebcamFacialEngine {
+        &self.engine
+    }
+
+    // Identifier of plugin instance (config_camera_id), same as camera_id of its data and events
+    pub const fn camera_id(&self) -> u32 {
+        self.engine.camera_id()
+    }
+
+    // Switch to other webcam device, running capture is stopped and started again on new device
+    pub fn set_device(&mut self, device: u32) {
+        self.engine.set_device(device);
+        if self.engine.is_running() {
+            self.restart = true;
+            self.engine.request_stop();
+        }
+    }
+
+    pub const fn device(&self) -> u32 {
+        self.engine.device()
+    }
+
+    // Resolution camera delivers while capturing (selected from config_webcam_resolutions), None when stopped
+    pub fn resolution(&self) -> Option<UVec2> {
+        self.engine.resolution().map(UVec2::from)
+    }
+
+    // Format, resolution and framerate camera delivers after negotiation, None until first frame arrives
+    pub fn active_config(&self) -> Option<ActiveCameraConfig> {
+        self.engine.active_config()
+    }
+
+    // Adjustable settings of configured webcam device (brightness, exposure...), Linux only
+    pub fn controls(&self) -> Result<Vec<WebcamControl>, WebcamFacialError> {
+        self.engine.controls()
+    }
+
+    // Set webcam control by name from controls(), applies immediately also while capturing
+    pub fn set_control(&self, name: &str, value: i64) -> Result<(), WebcamFacialError> {
+        self.engine.set_control(name, value)
+    }
+
+    // Switch all detector settings at once, takes effect on next detection
+    pub fn set_preset(&self, preset: DetectorPreset) {
+        self.engine.set_preset(preset);
+    }
+
+    // Preset matching current detector settings, None after thresholds were changed separately
+    pub fn preset(&self) -> Option<DetectorPreset> {
+        self.engine.preset()
+    }
+
+    pub fn pyramid_scale(&self) -> f32 {
+        self.engine.pyramid_scale()
+    }
+
+    pub fn slide_window(&self) -> (u32, u32) {
+        self.engine.slide_window()
+    }
+
+    // Minimal face size in pixels used by detector, takes effect on next detection
+    pub fn set_min_face_size(&self, min_face_size: u32) {
+        self.engine.set_min_face_size(min_face_size);
+    }
+
+    pub fn min_face_size(&self) -> u32 {
+        self.engine.min_face_size()
+    }
+
+    // Face score threshold used by detector (lower - more detections), takes effect on next detection
+    pub fn set_score_thresh(&self, score_thresh: f64) {
+        self.engine.set_score_thresh(score_thresh);
+    }
+
+    pub fn score_thresh(&self) -> f64 {
+        self.engine.score_thresh()
+    }
+
+    // Smoothing filter used by capture
+    pub const fn filter_type(&self) -> SmoothingFilterType {
+        self.engine.filter_type()
+    }
+
+    // Stop capture and wait until it finished and camera is released, false on timeout
+    // (warning tells how long camera capture is blocked). Blocks calling system meanwhile
+    pub fn stop_and_wait(&mut self, timeout: Duration) -> bool {
+        self.control = false;
+        self.restart = false;
+        self.engine.stop_and_wait(timeout)
+    }
+
+    // Stop detection and events, but keep camera open and detector loaded for instant resume
+    pub fn pause(&self) {
+        self.engine.pause();
+    }
+
+    pub fn resume(&self) {
+        self.engine.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.engine.is_paused()
+    }
+
+    // Drop filter history so next data starts from current face instead of gliding from old position
+    pub fn reset_filter(&self) {
+        self.engine.reset_filter();
+    }
+
+    // Data of last WebcamFacialDataEvent, None before first event and after capture finished
+    pub const fn last_data(&self) -> Option<WebcamFacialData> {
+        self.last_emitted_data
+    }
+
+    // Single detection without continuous capture, blocks until frame after warmup is captured and searched.
+    // Returns selected face unfiltered in configured coordinate mode, None if no face found.
+    // Fails while capture is running, camera is already in use
+    pub fn detect_once(&self) -> Result<Option<WebcamFacialData>, WebcamFacialError> {
+        self.engine.detect_once()
+    }
+
+    // Face is visible right now, changes together with face acquired/lost events
+    pub const fn face_present(&self) -> bool {
+        self.last_face_data.is_some()
+    }
+
+    // Current face center becomes neutral (0, 0) of center_x, center_y, normalized_x and normalized_y
+    // in following data, until reset. Returns false without visible face
+    pub fn calibrate(&mut self) -> bool {
+        let Some(data) = self.last_face_data else {
+            return false;
+        };
+        self.engine.add_calibration(&data);
+        true
+    }
+
+    pub fn reset_calibration(&mut self) {
+        self.engine.reset_calibration();
+    }
+
+    // Pause on focus loss, resume on focus unless paused already before focus was lost
+    fn apply_window_focus(&mut self, focused: bool) {
+        if focused {
+            if mem::take(&mut self.focus_paused) {
+                self.resume();
+            }
+        } else if !self.focus_paused && !self.is_paused() {
+            self.pause();
+            self.focus_paused = true;
+        }
+    }
+
+    // Face center in primary window pixels, kept zero without window or face
+    fn apply_window_coordinates(&self, mut data: WebcamFacialData) -> WebcamFacialData {
+        // Held frames (config_hold_last_on_dropout) keep last face box, so they keep its position too
+        if let Some(window_size) = self.window_size.filter(|_| data.normalized_width > 0.0) {
+            (data.window_x, data.window_y) = self
+                .engine
+                .config()
+                .config_coordinate_mode
+                .window_position(&data, window_size.into());
+        }
+        data
+    }
+}
+
+fn webcam_facial_task_runner(
+    mut controllers: Query<(&mut WebcamFacialController, &mut WebcamFacialStats)>,
+    mut plugin_events: WebcamFacialEventWriters,
+    mock: Option<Res<WebcamFacialMock>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    let window = windows.get_single().ok();
+    let window_size = window.map(|window| Vec2::new(window.width(), window.height()));
+    let window_focused = window.map(|window| window.focused);
+    for (mut webcam_facial, mut plugin_stats) in &mut controllers {
+        // Window may be resized anytime
+        webcam_facial.window_size = window_size.filter(|_| webcam_facial.config_window_coordinates);
+        if let Some(focused) = window_focused.filter(|_| webcam_facial.config_pause_on_focus_loss) {
+            webcam_facial.apply_window_focus(focused);
+        }
+        webcam_facial_controller_update(
+            &mut webcam_facial,
+            &mut plugin_stats,
+            &mut plugin_events,
+            mock.is_some(),
+        );
+    }
+}
+
+// Stop capture on app exit and wait for it, so cameras are released (LED off) before process ends
+fn webcam_facial_exit_cleanup(
+    mut exit_events: EventReader<AppExit>,
+    mut controllers: Query<&mut WebcamFacialController>,
+) {
+    if exit_events.is_empty() {
+        return;
+    }
+    exit_events.clear();
+    for mut webcam_facial in &mut controllers {
+        webcam_facial.control = false;
+        if webcam_facial.engine.has_thread() {
+            info!("Waiting for capture to stop.");
+            webcam_facial.engine.stop();
+        }
+    }
+}
+
+// Start/stop capture of one camera and turn its channel data into events
+fn webcam_facial_controller_update(
+    webcam_facial: &mut WebcamFacialController,
+    plugin_stats: &mut WebcamFacialStats,
+    plugin_events: &mut WebcamFacialEventWriters,
+    mock_active: bool,
+) {
+    let camera_id = webcam_facial.camera_id();
+    // If enabled and not running - start capture (previous one must be finished first),
+    // mock replaces camera capture
+    if webcam_facial.control && !webcam_facial.engine.has_thread() && !mock_active {
+        // Users without webcam still get working app, only first autostart is checked
+        if mem::take(&mut webcam_facial.autostart_check) && !webcam_facial.engine.camera_available()
+        {
+            warn!("No webcam found, capture not started.");
+            webcam_facial.control = false;
+            plugin_events
+                .no_camera
+                .send(WebcamFacialNoCameraAvailableEvent(camera_id));
+        } else if let Err(error) = webcam_facial.engine.start() {
+            error!("{}", error);
+            webcam_facial.control = false;
+            plugin_events
+                .error
+                .send(WebcamFacialErrorEvent(error, camera_id));
+        }
+    }
+    // If not enabled and capture is running set flag to stop
+    if !webcam_facial.control {
+        webcam_facial.engine.request_stop();
+    }
+    // Mock inserted while capturing replaces it, control stays enabled and capture starts again without mock
+    if mock_active && webcam_facial.control && webcam_facial.engine.has_thread() {
+        webcam_facial.restart = true;
+        webcam_facial.engine.request_stop();
+    }
+    // Before finished capture check, so started is always sent before stopped
+    if webcam_facial.engine.take_started() {
+        let resolution = webcam_facial.resolution().unwrap_or_default();
+        info!("Camera started at {}x{}.", resolution.x, resolution.y);
+        plugin_events
+            .started
+            .send(WebcamFacialStartedEvent(camera_id));
+        plugin_events
+            .resolution
+            .send(WebcamFacialResolutionEvent(resolution, camera_id));
+    }
+    let mut task_finished = false;
+    if let Some(status) = webcam_facial.engine.try_join() {
+        // Keep enabled if capture was stopped for restart
+        if webcam_facial.restart {
+            webcam_facial.restart = false;
+        } else {
+            webcam_facial.control = false;
+        }
+        task_finished = true;
+        plugin_events
+            .stopped
+            .send(WebcamFacialStoppedEvent(camera_id));
+        match status {
+            Ok(true) => info!("Camera stopped."),
+            Ok(false) => warn!("Plugin setup failed. Plugin self disabled."),
+            Err(error) => {
+                error!("{}", error);
+                plugin_events
+                    .error
+                    .send(WebcamFacialErrorEvent(error, camera_id));
+            }
+        }
+    }
+    while let Ok(stats) = webcam_facial.engine.stats_receiver.try_recv() {
+        *plugin_stats = stats;
+    }
+    while let Ok(error) = webcam_facial.engine.error_receiver.try_recv() {
+        plugin_events
+            .error
+            .send(WebcamFacialErrorEvent(error, camera_id));
+    }
+    #[cfg(feature = "blink")]
+    while let Ok(eyes) = webcam_facial.engine.blink_receiver.try_recv() {
+        trace!("Eyes changed {:?}", eyes);
+        plugin_events
+            .blink
+            .send(WebcamFacialBlinkEvent(eyes, camera_id));
+    }
+    while let Ok(all_data) = webcam_facial.engine.multi_receiver.try_recv() {
+        if !mock_active
+            && all_data
+                .first()
+                .is_some_and(|data| webcam_facial.engine.is_stale(data))
+        {
+            continue;
+        }
+        let all_data = all_data
+            .into_iter()
+            .map(|data| webcam_facial.apply_window_coordinates(data))
+            .collect();
+        plugin_events
+            .multi_face
+            .send(WebcamFacialMultiFaceEvent(all_data, camera_id));
+    }
+    while let Ok(diagnostics) = webcam_facial.engine.diagnostics_receiver.try_recv() {
+        plugin_events
+            .diagnostics
+            .send(WebcamFacialDiagnosticsEvent(diagnostics, camera_id));
+    }
+    while let Ok(data) = webcam_facial.engine.receiver.try_recv() {
+        // Acting on old detection is worse than skipping it, mock data is always fresh
+        if !mock_active && webcam_facial.engine.is_stale(&data) {
+            trace!("Dropping stale data {:?}", data);
+            continue;
+        }
+        let data = if mock_active {
+            webcam_facial.engine.apply_calibration(data)
+        } else {
+            data
+        };
+        let data = webcam_facial.apply_window_coordinates(data);
+        // Send events on face presence change
+        match (data.face_found, webcam_facial.last_face_data.take()) {
+            (true, None) => {
+                plugin_events
+                    .face_acquired
+                    .send(WebcamFacialFaceAcquiredEvent(data));
+            }
+            (false, Some(last_face_data)) => {
+                plugin_events
+                    .face_lost
+                    .send(WebcamFacialFaceLostEvent(last_face_data));
+            }
+            _ => {}
+        }
+        if data.face_found {
+            webcam_facial.last_face_data = Some(data);
+        }
+        // Skip event if data didn't change since last one
+        if webcam_facial.config_emit_on_change_only
+            && webcam_facial
+                .last_emitted_data
+                .is_some_and(|last_data| !data_changed(&last_data, &data))
+        {
+            continue;
+        }
+        webcam_facial.last_emitted_data = Some(data);
+        trace!("Send Bevy event {:?}", data);
+        plugin_events.data.send(WebcamFacialDataEvent(data));
+    }
+    // Face isn't tracked while paused
+    if webcam_facial.is_paused() {
+        if let Some(last_face_data) = webcam_facial.last_face_data.take() {
+            plugin_events
+                .face_lost
+                .send(WebcamFacialFaceLostEvent(last_face_data));
+        }
+    }
+    // Face can't be tracked anymore after capture finished
+    if task_finished {
+        *plugin_stats = WebcamFacialStats::default();
+        webcam_facial.last_emitted_data = None;
+        if let Some(last_face_data) = webcam_facial.last_face_data.take() {
+            plugin_events
+                .face_lost
+                .send(WebcamFacialFaceLostEvent(last_face_data));
+        }
+    }
+}
+
+// Data differs from last emitted one by more than EMIT_CHANGE_EPSILON, score (and so quality
+// normalized_score) and timestamp change every frame so they aren't compared
+fn data_changed(last_data: &WebcamFacialData, data: &WebcamFacialData) -> bool {
+    let fields = |data: &WebcamFacialData| {
+        [
+            data.center_x,
+            data.center_y,
+            data.x,
+            data.y,
+            data.width,
+            data.height,
+            data.normalized_x,
+            data.normalized_y,
+            data.normalized_width,
+            data.normalized_height,
+            data.yaw,
+            data.pitch,
+            data.velocity_x,
+            data.velocity_y,
+            data.window_x,
+            data.window_y,
+            data.quality.relative_size,
+        ]
+    };
+    (
+        last_data.face_found,
+        last_data.looking_at_screen,
+        last_data.id,
+        last_data.face_count,
+        last_data.quality.touches_edge,
+    ) != (
+        data.face_found,
+        data.looking_at_screen,
+        data.id,
+        data.face_count,
+        data.quality.touches_edge,
+    ) || fields(last_data)
+        .iter()
+        .zip(fields(data))
+        .any(|(last_value, value)| (last_value - value).abs() > EMIT_CHANGE_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WebcamFacialDataHold;
+
+    #[test]
+    fn held_frame_keeps_window_position() {
+        let mut webcam_facial = WebcamFacialPlugin {
+            config_window_coordinates: true,
+            config_webcam_autostart: false,
+            ..Default::default()
+        }
+        .controller();
+        webcam_facial.window_size = Some(Vec2::new(800.0, 600.0));
+        let mut hold = WebcamFacialDataHold::new(Some(Duration::from_secs(1)));
+        let found = webcam_facial.apply_window_coordinates(hold.apply(WebcamFacialData {
+            face_found: true,
+            x: -30.0,
+            y: -20.0,
+            width: 20.0,
+            height: 20.0,
+            normalized_width: 0.2,
+            normalized_height: 0.2667,
+            timestamp: Duration::from_millis(100),
+            ..Default::default()
+        }));
+        let held = webcam_facial.apply_window_coordinates(hold.apply(WebcamFacialData {
+            timestamp: Duration::from_millis(200),
+            ..Default::default()
+        }));
+        assert!(!held.face_found);
+        assert!(found.window_x > 0.0 && found.window_y > 0.0);
+        assert_eq!(
+            (held.window_x, held.window_y),
+            (found.window_x, found.window_y)
+        );
+        // Without held face there is no position
+        let lost = webcam_facial.apply_window_coordinates(hold.apply(WebcamFacialData {
+            timestamp: Duration::from_secs(2),
+            ..Default::default()
+        }));
+        assert_eq!((lost.window_x, lost.window_y), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::{
+    asset::{Assets, Handle},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        system::{Commands, Query, ResMut},
+    },
+    log::warn,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::Image,
+    },
+};
+use image::{buffer::ConvertBuffer, RgbImage, RgbaImage};
+
+use crate::{DetectedFace, WebcamFacialController};
+
+// Score at which score bar above face box has full box width
+const OVERLAY_FULL_SCORE: f64 = 20.0;
+
+// Handle of Image asset updated with every captured frame (config_expose_frame), on controller entity
+#[derive(Component, Clone, Debug)]
+pub struct WebcamFacialFrame {
+    pub image: Handle<Image>,
+}
+
+// Bevy textures have no RGB format, convert to RGBA
+pub fn frame_to_rgba(rgb_frame: &RgbImage) -> RgbaImage {
+    rgb_frame.convert()
+}
+
+// Draw face boxes with score bar above them (config_overlay_color), boxes may reach outside of frame
+pub fn draw_overlay(
+    rgba_frame: &mut RgbaImage,
+    faces: &[DetectedFace],
+    color: [u8; 4],
+    thickness: u32,
+) {
+    let thickness = thickness as i32;
+    for face in faces {
+        let (left, top) = (face.x, face.y);
+        let (right, bottom) = (left + face.width as i32, top + face.height as i32);
+        fill_rect(rgba_frame, (left, top), (right, top + thickness), color);
+        fill_rect(
+            rgba_frame,
+            (left, bottom - thickness),
+            (right, bottom),
+            color,
+        );
+        fill_rect(rgba_frame, (left, top), (left + thickness, bottom), color);
+        fill_rect(rgba_frame, (right - thickness, top), (right, bottom), color);
+        let score_width =
+            (f64::from(face.width) * (face.score / OVERLAY_FULL_SCORE).clamp(0.0, 1.0)) as i32;
+        fill_rect(
+            rgba_frame,
+            (left, top - 3 * thickness),
+            (left + score_width, top - 2 * thickness),
+            color,
+        );
+    }
+}
+
+// Fill pixels from min corner up to (excluding) max corner, clipped to frame
+fn fill_rect(rgba_frame: &mut RgbaImage, min: (i32, i32), max: (i32, i32), color: [u8; 4]) {
+    let (width, height) = rgba_frame.dimensions();
+    let clip = |value: i32, size: u32| value.clamp(0, size as i32) as u32;
+    for y in clip(min.1, height)..clip(max.1, height) {
+        for x in clip(min.0, width)..clip(max.0, width) {
+            rgba_frame.put_pixel(x, y, image::Rgba(color));
+        }
+    }
+}
+
+fn frame_to_image(rgba_frame: RgbaImage) -> Image {
+    let (width, height) = rgba_frame.dimensions();
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        rgba_frame.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+pub fn webcam_facial_frame_setup(
+    controllers: Query<(Entity, &WebcamFacialController)>,
+    images: Option<ResMut<Assets<Image>>>,
+    mut commands: Commands,
+) {
+    let mut exposed = controllers
+        .iter()
+        .filter(|(_, webcam_facial)| webcam_facial.engine().config().config_expose_frame)
+        .peekable();
+    if exposed.peek().is_none() {
+        return;
+    }
+    let Some(mut images) = images else {
+        warn!("No Image assets (missing ImagePlugin/DefaultPlugins), frame preview disabled.");
+        return;
+    };
+    for (entity, webcam_facial) in exposed {
+        let config = webcam_facial.engine().config();
+        let image = images.add(frame_to_image(RgbaImage::new(
+            config.config_webcam_width,
+            config.config_webcam_height,
+        )));
+        commands.entity(entity).insert(WebcamFacialFrame { image });
+    }
+}
+
+pub fn webcam_facial_frame_updater(
+    controllers: Query<(&WebcamFacialController, &WebcamFacialFrame)>,
+    images: Option<ResMut<Assets<Image>>>,
+) {
+    let Some(mut images) = images else {
+        return;
+    };
+    for (webcam_facial, frame) in &controllers {
+        // Only latest frame matters
+        let Some(rgba_frame) = webcam_facial.engine().frame_receiver.try_iter().last() else {
+            continue;
+        };
+        if let Some(image) = images.get_mut(&frame.image) {
+            *image = frame_to_image(rgba_frame);
+        }
+    }
+}
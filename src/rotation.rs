@@ -0,0 +1,49 @@
+use image::imageops;
+
+use crate::CapturedFrame;
+
+// Clockwise rotation turning captured frame upright, ex. for sideways mounted camera (config_rotation)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameRotation {
+    #[default]
+    None,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl FrameRotation {
+    // Width and height of frame are swapped
+    pub(crate) const fn swaps_axes(self) -> bool {
+        matches!(self, Self::Rotate90 | Self::Rotate270)
+    }
+
+    pub(crate) fn apply(self, frame: &mut CapturedFrame) {
+        match (self, frame) {
+            (Self::None, _) => {}
+            (Self::Rotate180, CapturedFrame::Rgb(frame)) => imageops::rotate180_in_place(frame),
+            (Self::Rotate180, CapturedFrame::Luma(frame)) => imageops::rotate180_in_place(frame),
+            (Self::Rotate90, CapturedFrame::Rgb(frame)) => *frame = imageops::rotate90(frame),
+            (Self::Rotate90, CapturedFrame::Luma(frame)) => *frame = imageops::rotate90(frame),
+            (Self::Rotate270, CapturedFrame::Rgb(frame)) => *frame = imageops::rotate270(frame),
+            (Self::Rotate270, CapturedFrame::Luma(frame)) => *frame = imageops::rotate270(frame),
+        }
+    }
+
+    // Field of view and pixel aspect of camera converted for rotated frame of given size,
+    // horizontal axis of frame turned by 90 degrees is vertical axis of camera
+    pub(crate) fn projection(
+        self,
+        fov: f32,
+        pixel_aspect: f32,
+        (width, height): (u32, u32),
+    ) -> (f32, f32) {
+        if !self.swaps_axes() {
+            return (fov, pixel_aspect);
+        }
+        // Same focal length, camera height (frame width) is in vertical pixels
+        let half_fov =
+            ((fov.to_radians() / 2.0).tan() * width as f32 / (height as f32 * pixel_aspect)).atan();
+        ((half_fov * 2.0).to_degrees(), 1.0 / pixel_aspect)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::WebcamFacialData;
+
+// Which detected face is tracked in WebcamFacialDataEvent (config_face_selection)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FaceSelection {
+    #[default]
+    HighestScore, // Most confident detection
+    Largest,       // Biggest face box, usually the person closest to camera
+    NearestCenter, // Face closest to frame center, usually the person in front of screen
+}
+
+impl FaceSelection {
+    // Index of selected face, faces are in CenterOriginYDown coordinates (first of equal scores wins)
+    pub(crate) fn select(self, faces: &[WebcamFacialData]) -> Option<usize> {
+        let indexed = faces.iter().enumerate();
+        match self {
+            Self::HighestScore => indexed
+                .min_by(|(_, a), (_, b)| b.score.total_cmp(&a.score))
+                .map(|(index, _)| index),
+            Self::Largest => indexed
+                .max_by_key(|(_, data)| data.area)
+                .map(|(index, _)| index),
+            Self::NearestCenter => indexed
+                .min_by(|(_, a), (_, b)| {
+                    a.normalized_x
+                        .hypot(a.normalized_y)
+                        .total_cmp(&b.normalized_x.hypot(b.normalized_y))
+                })
+                .map(|(index, _)| index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highest_score_not_first() {
+        let faces: Vec<WebcamFacialData> = [3.0, 9.5, 2.0, 9.5]
+            .into_iter()
+            .map(|score| WebcamFacialData {
+                score,
+                face_found: true,
+                ..Default::default()
+            })
+            .collect();
+        assert_eq!(FaceSelection::HighestScore.select(&faces), Some(1));
+        assert_eq!(FaceSelection::HighestScore.select(&[]), None);
+    }
+}
@@ -0,0 +1,102 @@
+use image::{codecs::gif::GifDecoder, AnimationDecoder, DynamicImage, ImageFormat, RgbImage};
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{FrameSource, WebcamFacialError};
+
+// Creates frame source inside capture thread each time capture starts
+pub type FrameSourceFactory =
+    Arc<dyn Fn() -> Result<Box<dyn FrameSource>, WebcamFacialError> + Send + Sync>;
+
+// Where captured frames come from (config_frame_source)
+#[derive(Clone, Default)]
+pub enum WebcamFacialSource {
+    #[default]
+    Camera, // Webcam config_webcam_device
+    ImageDirectory(PathBuf), // Images sorted by file name, looped at config_webcam_framerate
+    Gif(PathBuf),            // Animated GIF frames, looped at config_webcam_framerate
+    Custom(FrameSourceFactory), // Own FrameSource implementation
+}
+
+enum SequenceFrames {
+    Files(Vec<PathBuf>), // Loaded one by one, directory may be large
+    Decoded(Vec<RgbImage>),
+}
+
+// Frames read from files instead of camera for testing without hardware, paced like a camera
+pub struct ImageSequenceSource {
+    frames: SequenceFrames,
+    index: usize,
+    frame_interval: Duration,
+    next_frame_at: Instant,
+}
+
+impl ImageSequenceSource {
+    pub fn from_directory(path: &Path, framerate: u32) -> Result<Self, WebcamFacialError> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+            .map_err(|err| sequence_error(path, &err))?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|file| file.is_file() && ImageFormat::from_path(file).is_ok())
+            .collect();
+        if files.is_empty() {
+            return Err(sequence_error(path, &"no images found"));
+        }
+        files.sort();
+        Ok(Self::new(SequenceFrames::Files(files), framerate))
+    }
+
+    pub fn from_gif(path: &Path, framerate: u32) -> Result<Self, WebcamFacialError> {
+        let file = File::open(path).map_err(|err| sequence_error(path, &err))?;
+        let frames: Vec<RgbImage> = GifDecoder::new(BufReader::new(file))
+            .and_then(|decoder| decoder.into_frames().collect_frames())
+            .map_err(|err| sequence_error(path, &err))?
+            .into_iter()
+            .map(|frame| DynamicImage::ImageRgba8(frame.into_buffer()).to_rgb8())
+            .collect();
+        if frames.is_empty() {
+            return Err(sequence_error(path, &"no frames found"));
+        }
+        Ok(Self::new(SequenceFrames::Decoded(frames), framerate))
+    }
+
+    fn new(frames: SequenceFrames, framerate: u32) -> Self {
+        Self {
+            frames,
+            index: 0,
+            frame_interval: Duration::from_secs(1) / framerate.max(1),
+            next_frame_at: Instant::now(),
+        }
+    }
+}
+
+impl FrameSource for ImageSequenceSource {
+    fn next_frame(&mut self) -> Result<RgbImage, WebcamFacialError> {
+        // Deliver frames no faster than framerate
+        thread::sleep(self.next_frame_at.saturating_duration_since(Instant::now()));
+        self.next_frame_at = Instant::now() + self.frame_interval;
+        let frame = match &self.frames {
+            SequenceFrames::Files(files) => {
+                let file = &files[self.index % files.len()];
+                image::open(file)
+                    .map_err(|err| {
+                        WebcamFacialError::CaptureFailed(format!("'{}': {}", file.display(), err))
+                    })?
+                    .to_rgb8()
+            }
+            SequenceFrames::Decoded(frames) => frames[self.index % frames.len()].clone(),
+        };
+        self.index = self.index.wrapping_add(1);
+        Ok(frame)
+    }
+}
+
+fn sequence_error(path: &Path, err: &dyn std::fmt::Display) -> WebcamFacialError {
+    WebcamFacialError::CameraOpen(format!("'{}': {}", path.display(), err))
+}
@@ -0,0 +1,69 @@
+use std::{collections::VecDeque, time::Duration};
+
+// How many last detected frames are averaged
+const STATS_WINDOW_LENGTH: usize = 30;
+
+// Capture/detection loop performance, updated while capture is running (on controller entity)
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy", derive(bevy::ecs::component::Component))]
+pub struct WebcamFacialStats {
+    fps: f32,
+    detection_ms: f32,
+    dropped_frames: u32,
+}
+
+impl WebcamFacialStats {
+    // Rolling average of detected frames per second
+    pub const fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    // Rolling average of time spent converting and detecting one frame in milliseconds
+    pub const fn detection_ms(&self) -> f32 {
+        self.detection_ms
+    }
+
+    // Malformed frames (incomplete or undecodable) skipped since capture started
+    pub const fn dropped_frames(&self) -> u32 {
+        self.dropped_frames
+    }
+}
+
+// Collects per frame timings inside the task
+pub struct WebcamFacialStatsWindow(VecDeque<(Duration, Duration)>);
+
+impl WebcamFacialStatsWindow {
+    pub const fn new() -> Self {
+        Self(VecDeque::new())
+    }
+
+    pub fn push(
+        &mut self,
+        frame_time: Duration,
+        detection_time: Duration,
+        dropped_frames: u32,
+    ) -> WebcamFacialStats {
+        if self.0.len() >= STATS_WINDOW_LENGTH {
+            self.0.pop_front(); // Remove the oldest element
+        }
+        self.0.push_back((frame_time, detection_time));
+
+        let (frame_time_sum, detection_time_sum) = self.0.iter().fold(
+            (Duration::ZERO, Duration::ZERO),
+            |(frame_sum, detection_sum), (frame_time, detection_time)| {
+                (frame_sum + *frame_time, detection_sum + *detection_time)
+            },
+        );
+        let count = self.0.len() as f32;
+        let frame_time_average = frame_time_sum.as_secs_f32() / count;
+        WebcamFacialStats {
+            fps: if frame_time_average > 0.0 {
+                1.0 / frame_time_average
+            } else {
+                0.0
+            },
+            detection_ms: detection_time_sum.as_secs_f32() * 1000.0 / count,
+            dropped_frames,
+        }
+    }
+}
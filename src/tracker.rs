@@ -0,0 +1,62 @@
+use crate::WebcamFacialData;
+
+// Keeps face ids across frames by matching each face to nearest face of previous frame
+pub struct WebcamFacialTracker {
+    faces: Vec<(u32, f32, f32)>, // Id and normalized center of faces in previous frame
+    next_id: u32,
+    max_distance: f32,
+}
+
+impl WebcamFacialTracker {
+    // Faces moving more than max_distance (normalized units) between frames get new id, 0.0 - no tracking
+    pub const fn new(max_distance: f32) -> Self {
+        Self {
+            faces: Vec::new(),
+            next_id: 1,
+            max_distance,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.faces.clear();
+    }
+
+    // Set id of every face, ids start from 1 (0 - not tracked)
+    pub fn assign(&mut self, faces: &mut [WebcamFacialData]) {
+        if self.max_distance <= 0.0 {
+            return;
+        }
+        // Closest pairs are matched first, each previous face is matched at most once
+        let mut pairs: Vec<(f32, usize, usize)> = Vec::new();
+        for (face_index, face) in faces.iter().enumerate() {
+            for (previous_index, (_, previous_x, previous_y)) in self.faces.iter().enumerate() {
+                let distance =
+                    (face.normalized_x - previous_x).hypot(face.normalized_y - previous_y);
+                if distance <= self.max_distance {
+                    pairs.push((distance, face_index, previous_index));
+                }
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut face_matched = vec![false; faces.len()];
+        let mut previous_matched = vec![false; self.faces.len()];
+        for (_, face_index, previous_index) in pairs {
+            if face_matched[face_index] || previous_matched[previous_index] {
+                continue;
+            }
+            face_matched[face_index] = true;
+            previous_matched[previous_index] = true;
+            faces[face_index].id = self.faces[previous_index].0;
+        }
+        for (face, matched) in faces.iter_mut().zip(face_matched) {
+            if !matched {
+                face.id = self.next_id;
+                self.next_id = self.next_id.wrapping_add(1).max(1);
+            }
+        }
+        self.faces = faces
+            .iter()
+            .map(|face| (face.id, face.normalized_x, face.normalized_y))
+            .collect();
+    }
+}
@@ -0,0 +1,47 @@
+use bevy::{
+    math::{EulerRot, Quat, Vec2, Vec3},
+    transform::components::Transform,
+};
+
+use crate::WebcamFacialData;
+
+// What face position drives in facial_data_to_transform
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FacialTransformAxes {
+    #[default]
+    TranslateXY, // Move in screen plane (2D sprites, camera panning)
+    TranslateXZ, // Move over ground plane, face up/down moves forward/back
+    Rotate,      // Turn from yaw and pitch (head-look camera)
+}
+
+// Head tracking transform from face position relative to frame center, identity without face.
+// Sensitivity scales x and y: translation per normalized unit or rotation per radian.
+// Face moving to user's right moves/turns right, expects default CoordinateMode::CenterOriginYDown
+pub fn facial_data_to_transform(
+    data: &WebcamFacialData,
+    sensitivity: Vec2,
+    axes: FacialTransformAxes,
+) -> Transform {
+    if !data.face_found {
+        return Transform::IDENTITY;
+    }
+    // normalized_x is flipped (positive - user's right), normalized_y grows down
+    match axes {
+        FacialTransformAxes::TranslateXY => Transform::from_translation(Vec3::new(
+            data.normalized_x * sensitivity.x,
+            -data.normalized_y * sensitivity.y,
+            0.0,
+        )),
+        FacialTransformAxes::TranslateXZ => Transform::from_translation(Vec3::new(
+            data.normalized_x * sensitivity.x,
+            0.0,
+            data.normalized_y * sensitivity.y,
+        )),
+        FacialTransformAxes::Rotate => Transform::from_rotation(Quat::from_euler(
+            EulerRot::YXZ,
+            -data.yaw * sensitivity.x,
+            data.pitch * sensitivity.y,
+            0.0,
+        )),
+    }
+}